@@ -0,0 +1,248 @@
+use async_compression::tokio::bufread::{GzipDecoder, ZlibDecoder};
+use async_compression::tokio::write::{GzipEncoder, ZlibEncoder};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use crate::NbtParseError;
+use crate::parser::{nbt_parser, NbtCompression};
+use crate::types::NbtTag;
+use crate::writer::nbt_writer;
+
+/// Reading and writing NBT over tokio's `AsyncRead` and `AsyncWrite`, enabled with the `async`
+/// feature. Uncompressed readers consume exactly one tag, so they can be used on streams that
+/// continue after it, and compressed readers consume the compressed stream. Results and errors
+/// are the same as with [`nbt_parser`].
+pub mod nbt_async {
+    use super::*;
+
+    /// Reads one tag of uncompressed binary NBT.
+    pub async fn parse_binary<R: AsyncRead + Unpin>(reader: &mut R) -> Result<NbtTag, NbtParseError> {
+        nbt_parser::parse_binary(read_tag(reader).await?)
+    }
+
+    /// Reads gzip compressed binary NBT. The rest of the compressed stream is read to check
+    /// its trailer.
+    pub async fn parse_gzip<R: AsyncRead + Unpin>(reader: R) -> Result<NbtTag, NbtParseError> {
+        parse_decoded(GzipDecoder::new(BufReader::new(reader))).await
+    }
+
+    /// Reads zlib compressed binary NBT. The rest of the compressed stream is read to check
+    /// its trailer.
+    pub async fn parse_zlib<R: AsyncRead + Unpin>(reader: R) -> Result<NbtTag, NbtParseError> {
+        parse_decoded(ZlibDecoder::new(BufReader::new(reader))).await
+    }
+
+    /// Reads one tag from a decoder, then drains it so a corrupt checksum or a truncated
+    /// trailer is an error like in [`nbt_parser::decompress`].
+    async fn parse_decoded<R: AsyncRead + Unpin>(mut decoder: R) -> Result<NbtTag, NbtParseError> {
+        let data = read_tag(&mut decoder).await?;
+        tokio::io::copy(&mut decoder, &mut tokio::io::sink()).await?;
+        nbt_parser::parse_binary(data)
+    }
+
+    /// Reads gzip, zlib or uncompressed binary NBT, detecting the compression like
+    /// [`NbtCompression::detect`].
+    pub async fn parse_any<R: AsyncRead + Unpin>(mut reader: R) -> Result<NbtTag, NbtParseError> {
+        let mut header = [0; 2];
+        let mut len = 0;
+        while len < header.len() {
+            match reader.read(&mut header[len..]).await? {
+                0 => break,
+                read => len += read,
+            }
+        }
+        let header = &header[..len];
+        let mut reader = header.chain(reader);
+        match NbtCompression::detect(header) {
+            NbtCompression::None => parse_binary(&mut reader).await,
+            NbtCompression::Gzip => parse_gzip(reader).await,
+            NbtCompression::Zlib => parse_zlib(reader).await,
+        }
+    }
+
+    /// Writes uncompressed binary NBT. Tags that [`nbt_writer::write_binary`] rejects are
+    /// rejected before anything is written.
+    pub async fn write_binary<W: AsyncWrite + Unpin>(writer: &mut W, tag: &NbtTag) -> Result<(), NbtParseError> {
+        Ok(writer.write_all(&nbt_writer::write_binary(tag)?).await?)
+    }
+
+    /// Writes gzip compressed binary NBT. The compressed stream is finished by shutting down
+    /// the writer, like closing a file.
+    pub async fn write_gzip<W: AsyncWrite + Unpin>(writer: W, tag: &NbtTag) -> Result<(), NbtParseError> {
+        let mut encoder = GzipEncoder::new(writer);
+        write_binary(&mut encoder, tag).await?;
+        Ok(encoder.shutdown().await?)
+    }
+
+    /// Writes zlib compressed binary NBT. The compressed stream is finished by shutting down
+    /// the writer, like closing a file.
+    pub async fn write_zlib<W: AsyncWrite + Unpin>(writer: W, tag: &NbtTag) -> Result<(), NbtParseError> {
+        let mut encoder = ZlibEncoder::new(writer);
+        write_binary(&mut encoder, tag).await?;
+        Ok(encoder.shutdown().await?)
+    }
+
+    /// Writes binary NBT with a compression. Compressed streams shut down the writer.
+    pub async fn write_compressed<W: AsyncWrite + Unpin>(mut writer: W, tag: &NbtTag, compression: NbtCompression) -> Result<(), NbtParseError> {
+        match compression {
+            NbtCompression::None => write_binary(&mut writer, tag).await,
+            NbtCompression::Gzip => write_gzip(writer, tag).await,
+            NbtCompression::Zlib => write_zlib(writer, tag).await,
+        }
+    }
+}
+
+/// A list or compound whose payload is being read by [`read_tag`].
+enum Frame {
+    List { element_type: i8, remaining: usize },
+    Compound,
+}
+
+/// Reads the bytes of one named tag, following its structure without decoding values.
+async fn read_tag<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>, NbtParseError> {
+    let mut tag = TagReader { reader, data: vec![] };
+    let id = tag.byte().await?;
+    if id == 0 {
+        return Ok(tag.data);
+    }
+    tag.string().await?;
+    let mut stack = vec![];
+    let mut payload = Some(id);
+    loop {
+        if let Some(id) = payload.take() {
+            match id {
+                1 => tag.copy(1).await?,
+                2 => tag.copy(2).await?,
+                3 | 5 => tag.copy(4).await?,
+                4 | 6 => tag.copy(8).await?,
+                7 => tag.array(1).await?,
+                8 => tag.string().await?,
+                9 => {
+                    let element_type = tag.byte().await?;
+                    let remaining = tag.length().await?;
+                    if !(0..=12).contains(&element_type) {
+                        return Err(NbtParseError::UnknownNBT(element_type));
+                    }
+                    stack.push(Frame::List { element_type, remaining });
+                }
+                10 => stack.push(Frame::Compound),
+                11 => tag.array(4).await?,
+                12 => tag.array(8).await?,
+                id => return Err(NbtParseError::UnknownNBT(id)),
+            }
+        }
+        match stack.last_mut() {
+            None => return Ok(tag.data),
+            Some(Frame::List { remaining: 0, .. }) => {
+                stack.pop();
+            }
+            Some(Frame::List { element_type, remaining }) => {
+                *remaining -= 1;
+                payload = Some(*element_type);
+            }
+            Some(Frame::Compound) => match tag.byte().await? {
+                0 => {
+                    stack.pop();
+                }
+                id => {
+                    tag.string().await?;
+                    payload = Some(id);
+                }
+            },
+        }
+    }
+}
+
+struct TagReader<'a, R> {
+    reader: &'a mut R,
+    data: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> TagReader<'_, R> {
+    /// Copies `len` bytes. The buffer grows as data arrives, so a corrupt length can't
+    /// allocate more than the stream contains.
+    async fn copy(&mut self, len: usize) -> Result<(), NbtParseError> {
+        let read = (&mut *self.reader).take(len as u64).read_to_end(&mut self.data).await?;
+        if read < len {
+            return Err(NbtParseError::EndOfData);
+        }
+        Ok(())
+    }
+
+    async fn byte(&mut self) -> Result<i8, NbtParseError> {
+        self.copy(1).await?;
+        Ok(self.data[self.data.len() - 1] as i8)
+    }
+
+    fn last<const N: usize>(&self) -> [u8; N] {
+        self.data[self.data.len() - N..].try_into().unwrap()
+    }
+
+    async fn length(&mut self) -> Result<usize, NbtParseError> {
+        self.copy(4).await?;
+        Ok(i32::from_be_bytes(self.last()).max(0) as usize)
+    }
+
+    async fn string(&mut self) -> Result<(), NbtParseError> {
+        self.copy(2).await?;
+        let len = u16::from_be_bytes(self.last());
+        self.copy(len as usize).await
+    }
+
+    async fn array(&mut self, width: usize) -> Result<(), NbtParseError> {
+        let len = self.length().await?;
+        self.copy(len.checked_mul(width).ok_or(NbtParseError::EndOfData)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::nbt_async::*;
+    use crate::{nbt_parser, nbt_writer, NbtCompression, NbtParseError};
+    use crate::snbt_parser::parse_snbt;
+
+    #[tokio::test]
+    async fn async_round_trip() {
+        let mut tag = parse_snbt(r#"{a:1b,l:[[1s],[]],c:{d:[L;1L,2L],e:[I;]},s:"x",f:[1.5f]}"#).unwrap();
+        tag.set_name("root");
+        for compression in [NbtCompression::None, NbtCompression::Gzip, NbtCompression::Zlib] {
+            let mut data = vec![];
+            write_compressed(&mut data, &tag, compression).await.unwrap();
+            assert_eq!(nbt_parser::parse_any(data.clone()).unwrap(), tag);
+            assert_eq!(parse_any(data.as_slice()).await.unwrap(), tag);
+        }
+
+        let mut data = nbt_writer::write_binary(&tag).unwrap();
+        data.extend_from_slice(&[8, 0, 0, 0, 1, b'x']);
+        let mut reader = data.as_slice();
+        assert_eq!(parse_binary(&mut reader).await.unwrap(), tag);
+        assert_eq!(parse_binary(&mut reader).await.unwrap(), parse_snbt(r#""x""#).unwrap());
+        assert_eq!(parse_binary(&mut [0u8].as_slice()).await.unwrap(), crate::NbtTag::End);
+    }
+
+    #[tokio::test]
+    async fn async_errors() {
+        let data = nbt_writer::write_binary(&parse_snbt("{a:[I;1,2],b:1}").unwrap()).unwrap();
+        for len in 0..data.len() {
+            let sync = nbt_parser::parse_binary(data[..len].to_vec()).unwrap_err();
+            let async_ = parse_binary(&mut &data[..len]).await.unwrap_err();
+            assert_eq!(format!("{sync:?}"), format!("{async_:?}"));
+        }
+        let duplicate = [10, 0, 0, 1, 0, 1, b'a', 1, 1, 0, 1, b'a', 2, 0];
+        assert!(matches!(parse_binary(&mut duplicate.as_slice()).await, Err(NbtParseError::DuplicateKey(_))));
+        assert!(matches!(parse_binary(&mut [9, 0, 0, 0, 0, 0, 0, 1].as_slice()).await, Err(NbtParseError::UnknownNBT(0))));
+
+        let tag = parse_snbt("{a:[I;1,2],b:1}").unwrap();
+        for compression in [NbtCompression::Gzip, NbtCompression::Zlib] {
+            let mut data = vec![];
+            write_compressed(&mut data, &tag, compression).await.unwrap();
+            let mut corrupt = data.clone();
+            // The last bytes are the gzip size or the zlib checksum
+            *corrupt.last_mut().unwrap() ^= 1;
+            let truncated = &data[..data.len() - 2];
+            for data in [corrupt.as_slice(), truncated] {
+                // flate2 and async-compression word their errors differently
+                assert!(matches!(nbt_parser::parse_any(data.to_vec()), Err(NbtParseError::Io(_))));
+                assert!(matches!(parse_any(data).await, Err(NbtParseError::Io(_))));
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use inbt::snbt_parser::parse_snbt;
+use inbt::snbt_writer::to_snbt_pretty;
+use inbt::{nbt_parser, nbt_writer, NbtCompression, NbtPath, NbtTag, TreeOptions};
+
+const USAGE: &str = "\
+Usage:
+    inbt print <file>                   Print a file as SNBT
+    inbt tree <file>                    Print a file as a tree with one tag per line
+    inbt get <file> <path>              Print the values matching a path
+    inbt set <file> <path> <snbt>       Set the values matching a path, in place or to -o
+    inbt convert <input> <output>       Convert a file, the format is taken from the extension
+
+Options:
+    --input-format <binary|snbt|json>   Format of the input, detected by default
+    --format <binary|snbt|json>         Format of the output
+    --compression <none|gzip|zlib>      Compression of binary output, the input's by default,
+                                        or gzip if the input isn't binary
+    --little-endian                     Read binary input as little-endian
+    --endian <big|little>               Byte order of binary output, the input's by default
+    --lossy                             Read and write plain JSON without types
+    -o, --output <file>                 Output file of set
+    --depth <n>                         Depth up to which tree shows children
+    --color                             Color the output of tree";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Binary,
+    Snbt,
+    Json,
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "binary" | "nbt" | "dat" => Ok(Format::Binary),
+            "snbt" => Ok(Format::Snbt),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format {name}")),
+        }
+    }
+
+    /// Takes the format from the extension of a path, which is binary unless it's `.snbt`
+    /// or `.json`.
+    fn from_path(path: &str) -> Format {
+        match path.rsplit_once('.') {
+            Some((_, extension)) => Format::parse(&extension.to_lowercase()).unwrap_or(Format::Binary),
+            None => Format::Binary,
+        }
+    }
+}
+
+/// Options given on the command line.
+#[derive(Default)]
+struct Options {
+    args: Vec<String>,
+    input_format: Option<Format>,
+    format: Option<Format>,
+    compression: Option<NbtCompression>,
+    little_endian_input: bool,
+    little_endian: Option<bool>,
+    lossy: bool,
+    output: Option<String>,
+    tree: TreeOptions,
+    help: bool,
+}
+
+impl Options {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            let mut value = |option: &str| args.next().ok_or_else(|| format!("missing value for {option}"));
+            match arg.as_str() {
+                "--input-format" => options.input_format = Some(Format::parse(&value(&arg)?)?),
+                "--format" => options.format = Some(Format::parse(&value(&arg)?)?),
+                "--compression" => options.compression = Some(match value(&arg)?.as_str() {
+                    "none" => NbtCompression::None,
+                    "gzip" => NbtCompression::Gzip,
+                    "zlib" => NbtCompression::Zlib,
+                    other => return Err(format!("unknown compression {other}")),
+                }),
+                "--little-endian" => options.little_endian_input = true,
+                "--endian" => options.little_endian = Some(match value(&arg)?.as_str() {
+                    "big" => false,
+                    "little" => true,
+                    other => return Err(format!("unknown byte order {other}")),
+                }),
+                "--lossy" => options.lossy = true,
+                "-o" | "--output" => options.output = Some(value(&arg)?),
+                "--depth" => {
+                    let depth = value(&arg)?;
+                    options.tree.max_depth = Some(depth.parse().map_err(|_| format!("invalid depth {depth}"))?);
+                }
+                "--color" => options.tree.colors = true,
+                "-h" | "--help" => options.help = true,
+                _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("unknown option {arg}\n\n{USAGE}")),
+                _ => options.args.push(arg),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// A parsed file and how it was stored, so it can be written back the same way.
+struct Input {
+    tag: NbtTag,
+    format: Format,
+    compression: NbtCompression,
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("inbt: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let options = Options::parse(env::args().skip(1))?;
+    if options.help {
+        println!("{USAGE}");
+        return Ok(());
+    }
+    let args: Vec<&str> = options.args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["print", file] => {
+            let input = read(file, &options)?;
+            let output = write(&input.tag, options.format.unwrap_or(Format::Snbt), &input, &options)?;
+            io::stdout().write_all(&output).map_err(|e| e.to_string())
+        }
+        ["tree", file] => {
+            let input = read(file, &options)?;
+            print!("{}", input.tag.to_tree_with_options(&options.tree));
+            Ok(())
+        }
+        ["get", file, path] => {
+            let input = read(file, &options)?;
+            let path: NbtPath = path.parse().map_err(|e| format!("{e}"))?;
+            let format = options.format.unwrap_or(Format::Snbt);
+            for value in path.get(&input.tag).map_err(|e| e.to_string())? {
+                let output = write(&value.to_tag(""), format, &input, &options)?;
+                io::stdout().write_all(&output).map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }
+        ["set", file, path, value] => {
+            let mut input = read(file, &options)?;
+            let path: NbtPath = path.parse().map_err(|e| format!("{e}"))?;
+            let value = parse_snbt(value).map_err(|e| format!("invalid value: {e}"))?;
+            let count = path.set(&mut input.tag, value).map_err(|e| e.to_string())?;
+            let output = options.output.as_deref().unwrap_or(file);
+            let format = options.format.unwrap_or(input.format);
+            fs::write(output, write(&input.tag, format, &input, &options)?).map_err(|e| format!("{output}: {e}"))?;
+            eprintln!("set {count} value(s)");
+            Ok(())
+        }
+        ["convert", file, output] => {
+            let input = read(file, &options)?;
+            let format = options.format.unwrap_or_else(|| Format::from_path(output));
+            fs::write(output, write(&input.tag, format, &input, &options)?).map_err(|e| format!("{output}: {e}"))
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// Reads a file, detecting its compression and format unless `--input-format` is given.
+/// Binary is tried first, then JSON and SNBT.
+fn read(file: &str, options: &Options) -> Result<Input, String> {
+    let data = fs::read(file).map_err(|e| format!("{file}: {e}"))?;
+    let (data, compression) = nbt_parser::decompress(data).map_err(|e| format!("{file}: {e}"))?;
+    let parse_binary = |data: Vec<u8>| match options.little_endian_input {
+        true => nbt_parser::parse_binary_le(data),
+        false => nbt_parser::parse_binary(data),
+    };
+    let (tag, format) = match options.input_format {
+        Some(Format::Binary) => (parse_binary(data).map_err(|e| format!("{file}: {e}"))?, Format::Binary),
+        Some(format) => (parse_text(&text(file, data)?, format, options)?, format),
+        None => match data.first() {
+            Some(0..=12) => match parse_binary(data.clone()) {
+                Ok(tag) => (tag, Format::Binary),
+                Err(_) => detect_text(&text(file, data)?, options)?,
+            },
+            _ => detect_text(&text(file, data)?, options)?,
+        },
+    };
+    Ok(Input { tag, format, compression })
+}
+
+fn text(file: &str, data: Vec<u8>) -> Result<String, String> {
+    String::from_utf8(data).map_err(|_| format!("{file}: not binary NBT, SNBT or JSON"))
+}
+
+fn detect_text(text: &str, options: &Options) -> Result<(NbtTag, Format), String> {
+    if cfg!(feature = "json") {
+        if let Ok(tag) = parse_text(text, Format::Json, options) {
+            return Ok((tag, Format::Json));
+        }
+    }
+    Ok((parse_text(text, Format::Snbt, options)?, Format::Snbt))
+}
+
+fn parse_text(text: &str, format: Format, options: &Options) -> Result<NbtTag, String> {
+    match format {
+        Format::Binary => unreachable!("binary input is parsed as bytes"),
+        Format::Snbt => parse_snbt(text.trim()).map_err(|e| format!("invalid SNBT: {e}")),
+        Format::Json => parse_json(text, options),
+    }
+}
+
+#[cfg(feature = "json")]
+fn parse_json(text: &str, options: &Options) -> Result<NbtTag, String> {
+    use inbt::nbt_json::{from_json, from_json_lossy};
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("invalid JSON: {e}"))?;
+    match options.lossy {
+        true => from_json_lossy(&value),
+        false => from_json(&value),
+    }.map_err(|e| format!("invalid JSON: {e}"))
+}
+
+#[cfg(not(feature = "json"))]
+fn parse_json(_: &str, _: &Options) -> Result<NbtTag, String> {
+    Err("JSON support requires the json feature".to_string())
+}
+
+/// Writes a tag in a format. Binary output keeps the compression and byte order of the
+/// input unless options override them, and is gzip compressed when converted from text.
+fn write(tag: &NbtTag, format: Format, input: &Input, options: &Options) -> Result<Vec<u8>, String> {
+    match format {
+        Format::Binary => {
+            let little_endian = options.little_endian.unwrap_or(options.little_endian_input && input.format == Format::Binary);
+            let data = match little_endian {
+                true => nbt_writer::write_binary_le(tag),
+                false => nbt_writer::write_binary(tag),
+            }.map_err(|e| e.to_string())?;
+            let compression = options.compression.unwrap_or(match input.format {
+                Format::Binary => input.compression,
+                _ => NbtCompression::Gzip,
+            });
+            nbt_writer::compress(&data, compression).map_err(|e| e.to_string())
+        }
+        Format::Snbt => Ok(format!("{}\n", to_snbt_pretty(tag)).into_bytes()),
+        Format::Json => write_json(tag, options),
+    }
+}
+
+#[cfg(feature = "json")]
+fn write_json(tag: &NbtTag, options: &Options) -> Result<Vec<u8>, String> {
+    use inbt::nbt_json::{to_json, to_json_lossy};
+    let value = match options.lossy {
+        true => to_json_lossy(tag),
+        false => to_json(tag),
+    };
+    let json = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    Ok(format!("{json}\n").into_bytes())
+}
+
+#[cfg(not(feature = "json"))]
+fn write_json(_: &NbtTag, _: &Options) -> Result<Vec<u8>, String> {
+    Err("JSON support requires the json feature".to_string())
+}
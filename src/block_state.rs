@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::NbtParseError;
+use crate::NbtParseError::{InvalidValue, WrongType};
+use crate::model::{compound, field, optional};
+use crate::types::NbtTag;
+
+/// Namespace used for block names that don't specify one.
+pub const DEFAULT_NAMESPACE: &str = "minecraft";
+
+/// A block name together with its properties, e.g. `minecraft:oak_log[axis=y]`.
+/// Properties are kept sorted by name, so equal states always compare, hash and format equally.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockState {
+    name: String,
+    properties: BTreeMap<String, String>,
+}
+
+impl BlockState {
+    /// Creates a block state without properties. Names without a namespace are put in the
+    /// `minecraft` namespace.
+    pub fn new<S: Into<String>>(name: S) -> BlockState {
+        let name = name.into();
+        let name = if name.contains(':') {
+            name
+        } else {
+            format!("{DEFAULT_NAMESPACE}:{name}")
+        };
+        BlockState { name, properties: BTreeMap::new() }
+    }
+
+    pub fn with_property<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> BlockState {
+        self.set_property(key, value);
+        self
+    }
+
+    /// The namespaced block name, e.g. `minecraft:oak_log`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn namespace(&self) -> &str {
+        self.name.split_once(':').map_or(DEFAULT_NAMESPACE, |(namespace, _)| namespace)
+    }
+
+    /// The block name without its namespace, e.g. `oak_log`.
+    pub fn path(&self) -> &str {
+        self.name.split_once(':').map_or(&self.name, |(_, path)| path)
+    }
+
+    pub fn properties(&self) -> &BTreeMap<String, String> {
+        &self.properties
+    }
+
+    pub fn get_property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(|value| value.as_str())
+    }
+
+    pub fn set_property<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) -> Option<String> {
+        self.properties.insert(key.into(), value.into())
+    }
+
+    pub fn remove_property(&mut self, key: &str) -> Option<String> {
+        self.properties.remove(key)
+    }
+
+    /// Reads a palette entry compound like `{Name:"minecraft:oak_log", Properties:{axis:"y"}}`.
+    pub fn from_tag(tag: &NbtTag) -> Result<BlockState, NbtParseError> {
+        let mut state = BlockState::new(tag.get_string("Name")?);
+        if let Some(properties) = optional(field(compound(tag)?, "Properties", "compound", NbtTag::as_compound))? {
+            for property in properties.iter() {
+                if let NbtTag::String(key, value) = property {
+                    state.properties.insert(key.clone(), value.clone());
+                } else {
+                    return Err(WrongType("string".to_string(), property.type_name()));
+                }
+            }
+        }
+        Ok(state)
+    }
+
+    /// Converts the state into a palette entry compound with the given name. The `Properties`
+    /// compound is left out when the state has no properties.
+    pub fn to_tag<S: Into<String>>(&self, name: S) -> NbtTag {
+        let mut tags = vec![NbtTag::String("Name".to_string(), self.name.clone())];
+        if !self.properties.is_empty() {
+            let properties = self.properties.iter()
+                .map(|(key, value)| NbtTag::String(key.clone(), value.clone()))
+                .collect();
+            tags.push(NbtTag::Compound("Properties".to_string(), properties));
+        }
+        NbtTag::Compound(name.into(), tags.into())
+    }
+}
+
+impl Display for BlockState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.properties.is_empty() {
+            let properties = self.properties.iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>();
+            write!(f, "[{}]", properties.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BlockState {
+    type Err = NbtParseError;
+
+    /// Parses the command syntax for block states, e.g. `oak_log[axis=y]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, properties) = match s.split_once('[') {
+            Some((name, rest)) => {
+                let properties = rest.strip_suffix(']')
+                    .ok_or_else(|| InvalidValue(format!("missing closing bracket in block state {s}")))?;
+                (name.trim(), Some(properties))
+            }
+            None => (s, None),
+        };
+        if name.is_empty() || name.contains(']') {
+            return Err(InvalidValue(format!("invalid block name in block state {s}")));
+        }
+        let mut state = BlockState::new(name);
+        for property in properties.into_iter().flat_map(|p| p.split(',')) {
+            if property.trim().is_empty() {
+                continue;
+            }
+            let special = |part: &str| part.contains(['[', ']', '=']);
+            match property.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() && !special(key) && !special(value) => {
+                    state.set_property(key.trim(), value.trim());
+                }
+                _ => return Err(InvalidValue(format!("invalid property {property} in block state {s}"))),
+            }
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format() {
+        let state: BlockState = "oak_log[axis=y]".parse().unwrap();
+        assert_eq!(state.name(), "minecraft:oak_log");
+        assert_eq!(state.path(), "oak_log");
+        assert_eq!(state.get_property("axis"), Some("y"));
+        assert_eq!(state.to_string(), "minecraft:oak_log[axis=y]");
+
+        let stairs: BlockState = "minecraft:oak_stairs[waterlogged=false, facing=east]".parse().unwrap();
+        assert_eq!(stairs.to_string(), "minecraft:oak_stairs[facing=east,waterlogged=false]");
+        assert_eq!("mod:thing".parse::<BlockState>().unwrap().namespace(), "mod");
+        assert!("stone[axis=y".parse::<BlockState>().is_err());
+        assert!("stone[axis]".parse::<BlockState>().is_err());
+        assert!("stone[a=b][c=d]".parse::<BlockState>().is_err());
+        assert!("stone[a=b=c]".parse::<BlockState>().is_err());
+        assert!("stone[a[b=c]".parse::<BlockState>().is_err());
+        assert!("".parse::<BlockState>().is_err());
+    }
+
+    #[test]
+    fn tag_round_trip() {
+        let tag = NbtTag::Compound("".to_string(), vec![
+            NbtTag::String("Name".to_string(), "minecraft:oak_log".to_string()),
+            NbtTag::Compound("Properties".to_string(), vec![
+                NbtTag::String("axis".to_string(), "y".to_string()),
+            ].into()),
+        ].into());
+        let state = BlockState::from_tag(&tag).unwrap();
+        assert_eq!(state, BlockState::new("oak_log").with_property("axis", "y"));
+        assert_eq!(state.to_tag(""), tag);
+
+        let air = NbtTag::Compound("".to_string(), vec![
+            NbtTag::String("Name".to_string(), "minecraft:air".to_string()),
+        ].into());
+        assert_eq!(BlockState::from_tag(&air).unwrap().to_tag(""), air);
+    }
+
+    #[test]
+    fn hashable() {
+        let mut counts = std::collections::HashMap::new();
+        *counts.entry("stone".parse::<BlockState>().unwrap()).or_insert(0) += 1;
+        *counts.entry(BlockState::new("minecraft:stone")).or_insert(0) += 1;
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&BlockState::new("stone")], 2);
+    }
+}
@@ -0,0 +1,240 @@
+use std::ops::Deref;
+use crate::NbtParseError;
+use crate::compound::NbtCompound;
+use crate::list::NbtList;
+use crate::types::NbtTag;
+use crate::parser::Scanner;
+use crate::visitor::NbtArray;
+
+/// A tree borrowing its names, strings and arrays from the binary data it was parsed from,
+/// see [`nbt_parser::parse_borrowed`](crate::nbt_parser::parse_borrowed). Arrays are decoded
+/// when accessed. [`NbtTagRef::to_tag`] converts it to an owned tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtTagRef<'a> {
+    End,
+    Byte(&'a str, i8),
+    Short(&'a str, i16),
+    Int(&'a str, i32),
+    Long(&'a str, i64),
+    Float(&'a str, f32),
+    Double(&'a str, f64),
+    ByteArray(&'a str, NbtArray<'a>),
+    String(&'a str, &'a str),
+    List(&'a str, NbtListRef<'a>),
+    /// Duplicate keys aren't checked, lookups return the first child with a name.
+    Compound(&'a str, Vec<NbtTagRef<'a>>),
+    IntArray(&'a str, NbtArray<'a>),
+    LongArray(&'a str, NbtArray<'a>),
+}
+
+/// The elements of a borrowed list, with their type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtListRef<'a> {
+    element_type: i8,
+    elements: Vec<NbtTagRef<'a>>,
+}
+
+impl<'a> NbtListRef<'a> {
+    pub fn element_type(&self) -> i8 {
+        self.element_type
+    }
+}
+
+impl<'a> Deref for NbtListRef<'a> {
+    type Target = [NbtTagRef<'a>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.elements
+    }
+}
+
+impl<'a> NbtTagRef<'a> {
+    pub fn name(&self) -> &'a str {
+        match self {
+            NbtTagRef::End => "",
+            NbtTagRef::Byte(name, _) => name,
+            NbtTagRef::Short(name, _) => name,
+            NbtTagRef::Int(name, _) => name,
+            NbtTagRef::Long(name, _) => name,
+            NbtTagRef::Float(name, _) => name,
+            NbtTagRef::Double(name, _) => name,
+            NbtTagRef::ByteArray(name, _) => name,
+            NbtTagRef::String(name, _) => name,
+            NbtTagRef::List(name, _) => name,
+            NbtTagRef::Compound(name, _) => name,
+            NbtTagRef::IntArray(name, _) => name,
+            NbtTagRef::LongArray(name, _) => name,
+        }
+    }
+
+    /// The numeric id used for this type in binary NBT.
+    pub fn type_id(&self) -> i8 {
+        match self {
+            NbtTagRef::End => 0,
+            NbtTagRef::Byte(_, _) => 1,
+            NbtTagRef::Short(_, _) => 2,
+            NbtTagRef::Int(_, _) => 3,
+            NbtTagRef::Long(_, _) => 4,
+            NbtTagRef::Float(_, _) => 5,
+            NbtTagRef::Double(_, _) => 6,
+            NbtTagRef::ByteArray(_, _) => 7,
+            NbtTagRef::String(_, _) => 8,
+            NbtTagRef::List(_, _) => 9,
+            NbtTagRef::Compound(_, _) => 10,
+            NbtTagRef::IntArray(_, _) => 11,
+            NbtTagRef::LongArray(_, _) => 12,
+        }
+    }
+
+    /// Returns the named child of a compound.
+    pub fn get(&self, name: &str) -> Option<&NbtTagRef<'a>> {
+        match self {
+            NbtTagRef::Compound(_, tags) => tags.iter().find(|tag| tag.name() == name),
+            _ => None,
+        }
+    }
+
+    /// Returns an element of a list.
+    pub fn index(&self, index: usize) -> Option<&NbtTagRef<'a>> {
+        match self {
+            NbtTagRef::List(_, list) => list.get(index),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            NbtTagRef::String(_, value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value of a byte, short, int or long, widened to `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            NbtTagRef::Byte(_, value) => Some(*value as i64),
+            NbtTagRef::Short(_, value) => Some(*value as i64),
+            NbtTagRef::Int(_, value) => Some(*value as i64),
+            NbtTagRef::Long(_, value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The view of a byte, int or long array.
+    pub fn as_array(&self) -> Option<NbtArray<'a>> {
+        match self {
+            NbtTagRef::ByteArray(_, array) | NbtTagRef::IntArray(_, array) | NbtTagRef::LongArray(_, array) => Some(*array),
+            _ => None,
+        }
+    }
+
+    /// Copies the tree into an owned [`NbtTag`]. Fails if a compound has duplicate keys, like
+    /// [`nbt_parser::parse_binary`](crate::nbt_parser::parse_binary).
+    pub fn to_tag(&self) -> Result<NbtTag, NbtParseError> {
+        let name = self.name().to_string();
+        Ok(match self {
+            NbtTagRef::End => NbtTag::End,
+            NbtTagRef::Byte(_, value) => NbtTag::Byte(name, *value),
+            NbtTagRef::Short(_, value) => NbtTag::Short(name, *value),
+            NbtTagRef::Int(_, value) => NbtTag::Int(name, *value),
+            NbtTagRef::Long(_, value) => NbtTag::Long(name, *value),
+            NbtTagRef::Float(_, value) => NbtTag::Float(name, *value),
+            NbtTagRef::Double(_, value) => NbtTag::Double(name, *value),
+            NbtTagRef::String(_, value) => NbtTag::String(name, value.to_string()),
+            NbtTagRef::List(_, list) => {
+                let mut elements = NbtList::with_capacity(list.element_type, list.len());
+                for element in list.iter() {
+                    elements.push(element.to_tag()?)?;
+                }
+                NbtTag::List(name, elements)
+            }
+            NbtTagRef::Compound(_, tags) => {
+                let mut compound = NbtCompound::new();
+                for tag in tags {
+                    compound.push_unique(tag.to_tag()?)?;
+                }
+                NbtTag::Compound(name, compound)
+            }
+            NbtTagRef::ByteArray(_, array) | NbtTagRef::IntArray(_, array) | NbtTagRef::LongArray(_, array) => array.to_tag(name),
+        })
+    }
+}
+
+impl<'a> Scanner<'a> {
+    /// Reads a named tag, borrowing from the data.
+    pub(crate) fn named_ref(&mut self) -> Result<NbtTagRef<'a>, NbtParseError> {
+        let id = self.type_id()?;
+        if id == 0 {
+            return Ok(NbtTagRef::End);
+        }
+        let name = self.string()?;
+        self.payload_ref(id, name)
+    }
+
+    fn payload_ref(&mut self, id: i8, name: &'a str) -> Result<NbtTagRef<'a>, NbtParseError> {
+        Ok(match id {
+            1 => NbtTagRef::Byte(name, self.type_id()?),
+            2 => NbtTagRef::Short(name, i16::from_be_bytes(self.bytes()?)),
+            3 => NbtTagRef::Int(name, i32::from_be_bytes(self.bytes()?)),
+            4 => NbtTagRef::Long(name, i64::from_be_bytes(self.bytes()?)),
+            5 => NbtTagRef::Float(name, f32::from_be_bytes(self.bytes()?)),
+            6 => NbtTagRef::Double(name, f64::from_be_bytes(self.bytes()?)),
+            7 => NbtTagRef::ByteArray(name, self.array(7, 1)?),
+            8 => NbtTagRef::String(name, self.string()?),
+            9 => {
+                let (element_type, len) = self.list_header()?;
+                let mut elements = Vec::with_capacity(self.list_capacity(element_type, len));
+                for _ in 0..len {
+                    elements.push(self.payload_ref(element_type, "")?);
+                }
+                NbtTagRef::List(name, NbtListRef { element_type, elements })
+            }
+            10 => {
+                let mut tags = vec![];
+                loop {
+                    match self.named_ref()? {
+                        NbtTagRef::End => break,
+                        tag => tags.push(tag),
+                    }
+                }
+                NbtTagRef::Compound(name, tags)
+            }
+            11 => NbtTagRef::IntArray(name, self.array(11, 4)?),
+            12 => NbtTagRef::LongArray(name, self.array(12, 8)?),
+            id => return Err(NbtParseError::UnknownNBT(id)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt_parser;
+    use crate::nbt_writer::write_binary;
+    use crate::snbt_parser::parse_snbt;
+
+    #[test]
+    fn borrowed_tree() {
+        let mut tag = parse_snbt(r#"{id:"minecraft:chest",x:-5,l:[{Slot:1b}],e:[],ba:[B;-1b],
+            heights:[L;1L,-2L,9000000000L],c:{f:0.5f,d:1.5d,s:3s,ia:[I;7]}}"#).unwrap();
+        tag.set_name("root");
+        let data = write_binary(&tag).unwrap();
+        let borrowed = nbt_parser::parse_borrowed(&data).unwrap();
+        assert_eq!(borrowed.name(), "root");
+        assert_eq!(borrowed.get("id").and_then(NbtTagRef::as_str), Some("minecraft:chest"));
+        assert_eq!(borrowed.get("x").and_then(NbtTagRef::as_i64), Some(-5));
+        assert_eq!(borrowed.get("l").and_then(|list| list.index(0)).and_then(|tag| tag.get("Slot")), Some(&NbtTagRef::Byte("Slot", 1)));
+        let heights = borrowed.get("heights").and_then(NbtTagRef::as_array).unwrap();
+        assert_eq!((heights.len(), heights.get(2)), (3, Some(9000000000)));
+        assert_eq!(borrowed.to_tag().unwrap(), tag);
+
+        assert!(matches!(nbt_parser::parse_borrowed(&data[..data.len() - 1]), Err(NbtParseError::EndOfData)));
+        let duplicate = [10, 0, 0, 1, 0, 1, b'a', 1, 1, 0, 1, b'a', 2, 0];
+        assert_eq!(nbt_parser::parse_borrowed(&duplicate).unwrap().get("a"), Some(&NbtTagRef::Byte("a", 1)));
+        assert!(matches!(nbt_parser::parse_borrowed(&duplicate).unwrap().to_tag(), Err(NbtParseError::DuplicateKey(_))));
+
+        let level = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/test_files/level.dat")).unwrap();
+        let (level, _) = nbt_parser::decompress(level).unwrap();
+        assert_eq!(nbt_parser::parse_borrowed(&level).unwrap().to_tag().unwrap(), nbt_parser::parse_binary(level.clone()).unwrap());
+    }
+}
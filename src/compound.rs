@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::ops::Deref;
+use crate::NbtParseError;
+use crate::types::NbtTag;
+use crate::value::NbtValueMut;
+
+/// The children of a compound tag. Children keep their insertion order, so a compound is
+/// written back exactly as it was read, while lookups by name go through a hash index.
+///
+/// Names are unique: inserting a child replaces any child with the same name. End tags only
+/// mark the end of a compound in binary NBT, so they can't be children. Mutable access
+/// to children only reaches their values, so names change only through [`NbtCompound::rename`]
+/// and the index always matches the children.
+#[derive(Clone, Default)]
+pub struct NbtCompound {
+    tags: Vec<NbtTag>,
+    index: HashMap<String, usize>,
+}
+
+impl NbtCompound {
+    pub fn new() -> NbtCompound {
+        NbtCompound::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> NbtCompound {
+        NbtCompound { tags: Vec::with_capacity(capacity), index: HashMap::with_capacity(capacity) }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NbtTag> {
+        self.index.get(name).map(|index| &self.tags[*index])
+    }
+
+    /// Returns mutable access to the value of the named child, which keeps its name and type.
+    pub fn get_mut(&mut self, name: &str) -> Option<NbtValueMut<'_>> {
+        let index = *self.index.get(name)?;
+        self.tags[index].as_value_mut()
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// Inserts a child. A child with the same name is replaced in place and returned,
+    /// otherwise the child is appended. Fails for End.
+    pub fn insert(&mut self, tag: NbtTag) -> Result<Option<NbtTag>, NbtParseError> {
+        check_child(&tag)?;
+        Ok(match self.index.get(tag.name()) {
+            Some(index) => Some(std::mem::replace(&mut self.tags[*index], tag)),
+            None => {
+                self.index.insert(tag.name().to_string(), self.tags.len());
+                self.tags.push(tag);
+                None
+            }
+        })
+    }
+
+    /// Appends a child, failing for End or if a child with the same name already exists.
+    pub fn push_unique(&mut self, tag: NbtTag) -> Result<(), NbtParseError> {
+        check_child(&tag)?;
+        if self.contains_key(tag.name()) {
+            return Err(NbtParseError::DuplicateKey(tag.name().to_string()));
+        }
+        self.index.insert(tag.name().to_string(), self.tags.len());
+        self.tags.push(tag);
+        Ok(())
+    }
+
+    /// Removes and returns the named child, keeping the order of the other children.
+    pub fn remove(&mut self, name: &str) -> Option<NbtTag> {
+        let index = self.index.remove(name)?;
+        let tag = self.tags.remove(index);
+        for (position, tag) in self.tags.iter().enumerate().skip(index) {
+            *self.index.get_mut(tag.name()).expect("children are indexed") = position;
+        }
+        Some(tag)
+    }
+
+    /// Renames a child, keeping its position. Another child already using the new name is
+    /// removed and returned.
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<Option<NbtTag>, NbtParseError> {
+        if !self.contains_key(name) {
+            return Err(NbtParseError::NoSuchValue(name.to_string()));
+        }
+        if name == new_name {
+            return Ok(None);
+        }
+        let replaced = self.remove(new_name);
+        let index = self.index.remove(name).expect("the child was found before");
+        self.tags[index].set_name(new_name);
+        self.index.insert(new_name.to_string(), index);
+        Ok(replaced)
+    }
+
+    /// Iterates over the names of the children with mutable access to their values.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, NbtValueMut<'_>)> {
+        self.tags.iter_mut().filter_map(NbtTag::name_and_value_mut)
+    }
+
+    pub fn into_vec(self) -> Vec<NbtTag> {
+        self.tags
+    }
+}
+
+fn check_child(tag: &NbtTag) -> Result<(), NbtParseError> {
+    match tag {
+        NbtTag::End => Err(NbtParseError::InvalidValue("end tags can't be children of a compound".to_string())),
+        _ => Ok(()),
+    }
+}
+
+impl Deref for NbtCompound {
+    type Target = [NbtTag];
+
+    fn deref(&self) -> &Self::Target {
+        &self.tags
+    }
+}
+
+impl PartialEq for NbtCompound {
+    fn eq(&self, other: &Self) -> bool {
+        self.tags == other.tags
+    }
+}
+
+impl Debug for NbtCompound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(&self.tags).finish()
+    }
+}
+
+impl From<Vec<NbtTag>> for NbtCompound {
+    /// Builds a compound from a list of children. Later children replace earlier children
+    /// with the same name, and End tags are left out.
+    fn from(tags: Vec<NbtTag>) -> Self {
+        tags.into_iter().collect()
+    }
+}
+
+impl FromIterator<NbtTag> for NbtCompound {
+    fn from_iter<T: IntoIterator<Item = NbtTag>>(iter: T) -> Self {
+        let mut compound = NbtCompound::new();
+        for tag in iter.into_iter().filter(|tag| *tag != NbtTag::End) {
+            compound.insert(tag).expect("End tags are filtered out");
+        }
+        compound
+    }
+}
+
+impl IntoIterator for NbtCompound {
+    type Item = NbtTag;
+    type IntoIter = std::vec::IntoIter<NbtTag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tags.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a NbtCompound {
+    type Item = &'a NbtTag;
+    type IntoIter = std::slice::Iter<'a, NbtTag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tags.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered_lookup() {
+        let mut compound = NbtCompound::from(vec![
+            NbtTag::Int("b".to_string(), 1),
+            NbtTag::Int("a".to_string(), 2),
+            NbtTag::Int("b".to_string(), 3),
+        ]);
+        assert_eq!(compound.len(), 2);
+        assert_eq!(compound[0], NbtTag::Int("b".to_string(), 3));
+        assert_eq!(compound.get("a"), Some(&NbtTag::Int("a".to_string(), 2)));
+
+        compound.insert(NbtTag::Int("c".to_string(), 4)).unwrap();
+        assert_eq!(compound.remove("b"), Some(NbtTag::Int("b".to_string(), 3)));
+        assert_eq!(compound.iter().map(|tag| tag.name()).collect::<Vec<_>>(), vec!["a", "c"]);
+        assert_eq!(compound.get("c"), Some(&NbtTag::Int("c".to_string(), 4)));
+        assert!(matches!(compound.push_unique(NbtTag::Byte("a".to_string(), 0)), Err(NbtParseError::DuplicateKey(_))));
+    }
+
+    #[test]
+    fn reject_end() {
+        let mut compound = NbtCompound::from(vec![
+            NbtTag::Byte("a".to_string(), 1),
+            NbtTag::End,
+            NbtTag::Byte("b".to_string(), 2),
+        ]);
+        assert_eq!(compound.iter().map(|tag| tag.name()).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!(matches!(compound.insert(NbtTag::End), Err(NbtParseError::InvalidValue(_))));
+        assert!(matches!(compound.push_unique(NbtTag::End), Err(NbtParseError::InvalidValue(_))));
+        assert_eq!(compound.len(), 2);
+
+        let tag = NbtTag::Compound("".to_string(), compound);
+        let data = crate::nbt_writer::write_binary(&tag).unwrap();
+        assert_eq!(crate::nbt_parser::parse_binary(data).unwrap(), tag);
+    }
+
+    #[test]
+    fn edit_children() {
+        let mut compound = NbtCompound::from(vec![
+            NbtTag::Int("a".to_string(), 1),
+            NbtTag::Int("b".to_string(), 2),
+            NbtTag::Int("c".to_string(), 3),
+        ]);
+        if let Some(NbtValueMut::Int(value)) = compound.get_mut("a") {
+            *value = 5;
+        }
+        for (name, mut value) in compound.iter_mut() {
+            if name == "c" {
+                value.set(NbtTag::Int(String::new(), 6)).unwrap();
+            }
+        }
+        assert_eq!(compound.rename("a", "z").unwrap(), None);
+        assert_eq!(compound.rename("z", "b").unwrap(), Some(NbtTag::Int("b".to_string(), 2)));
+        assert!(matches!(compound.rename("a", "d"), Err(NbtParseError::NoSuchValue(_))));
+        assert_eq!(compound.get("a"), None);
+        assert_eq!(compound.get("b"), Some(&NbtTag::Int("b".to_string(), 5)));
+        assert_eq!(compound.get("c"), Some(&NbtTag::Int("c".to_string(), 6)));
+        compound.insert(NbtTag::Int("a".to_string(), 7)).unwrap();
+        assert_eq!(compound.iter().map(|tag| tag.name()).collect::<Vec<_>>(), vec!["b", "c", "a"]);
+        compound.remove("b");
+        assert_eq!(compound.get("c"), Some(&NbtTag::Int("c".to_string(), 6)));
+        assert_eq!(compound.get("a"), Some(&NbtTag::Int("a".to_string(), 7)));
+    }
+}
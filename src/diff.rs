@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use crate::NbtParseError;
+use crate::NbtParseError::InvalidValue;
+use crate::compound::NbtCompound;
+use crate::list::NbtList;
+use crate::path::NbtPath;
+use crate::snbt::snbt_writer::to_snbt;
+use crate::types::NbtTag;
+use crate::value::NbtValue;
+
+/// Options for comparing trees with [`NbtDiff::with_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Fields identifying the elements of lists of compounds, like `Slot` or `UUID`. Lists
+    /// whose elements all have one of these fields, with unique values, are compared by that
+    /// field instead of by index. The first matching field is used.
+    pub list_keys: Vec<String>,
+}
+
+impl DiffOptions {
+    pub fn new() -> DiffOptions {
+        DiffOptions::default()
+    }
+
+    pub fn with_list_key<S: Into<String>>(mut self, key: S) -> DiffOptions {
+        self.list_keys.push(key.into());
+        self
+    }
+}
+
+/// A single difference between two trees. Values are stored without names, the name is
+/// the last part of the path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtChange {
+    Added { path: NbtPath, value: NbtTag },
+    Removed { path: NbtPath, value: NbtTag },
+    /// The value changed but kept its type.
+    Changed { path: NbtPath, old: NbtTag, new: NbtTag },
+    /// The value was replaced by a value of another type.
+    TypeChanged { path: NbtPath, old: NbtTag, new: NbtTag },
+}
+
+impl NbtChange {
+    pub fn path(&self) -> &NbtPath {
+        match self {
+            NbtChange::Added { path, .. } => path,
+            NbtChange::Removed { path, .. } => path,
+            NbtChange::Changed { path, .. } => path,
+            NbtChange::TypeChanged { path, .. } => path,
+        }
+    }
+
+    fn op(&self) -> &'static str {
+        match self {
+            NbtChange::Added { .. } => "add",
+            NbtChange::Removed { .. } => "remove",
+            NbtChange::Changed { .. } => "change",
+            NbtChange::TypeChanged { .. } => "type_change",
+        }
+    }
+}
+
+impl Display for NbtChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let path = match self.path().to_string() {
+            path if path.is_empty() => "<root>".to_string(),
+            path => path,
+        };
+        match self {
+            NbtChange::Added { value, .. } => write!(f, "+ {path}: {}", to_snbt(value)),
+            NbtChange::Removed { value, .. } => write!(f, "- {path}: {}", to_snbt(value)),
+            NbtChange::Changed { old, new, .. } => write!(f, "~ {path}: {} -> {}", to_snbt(old), to_snbt(new)),
+            NbtChange::TypeChanged { old, new, .. } => {
+                write!(f, "! {path}: {} {} -> {} {}", old.type_name(), to_snbt(old), new.type_name(), to_snbt(new))
+            }
+        }
+    }
+}
+
+/// The differences between two trees, e.g. two backups of a `player.dat`.
+///
+/// Displaying a diff gives one line per change, prefixed with `+` for added, `-` for
+/// removed, `~` for changed values and `!` for type changes. [`NbtDiff::to_tag`] gives a
+/// patch that can be stored as NBT.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NbtDiff {
+    pub changes: Vec<NbtChange>,
+}
+
+impl NbtDiff {
+    /// Compares two trees, comparing list elements by index.
+    pub fn new(old: &NbtTag, new: &NbtTag) -> NbtDiff {
+        NbtDiff::with_options(old, new, &DiffOptions::default())
+    }
+
+    pub fn with_options(old: &NbtTag, new: &NbtTag, options: &DiffOptions) -> NbtDiff {
+        let mut diff = NbtDiff::default();
+        diff.compare_tags(NbtPath::root(), old, new, options);
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Converts the diff into a patch, a list of compounds like
+    /// `{op:"change",path:"Data.Time",old:1L,new:2L}`. `add` and `remove` store the value in
+    /// `value`, `change` and `type_change` in `old` and `new`.
+    pub fn to_tag<S: Into<String>>(&self, name: S) -> NbtTag {
+        let changes = self.changes.iter().map(|change| {
+            let mut tags = vec![
+                NbtTag::String("op".to_string(), change.op().to_string()),
+                NbtTag::String("path".to_string(), change.path().to_string()),
+            ];
+            let named = |name: &str, tag: &NbtTag| {
+                let mut tag = tag.clone();
+                tag.set_name(name);
+                tag
+            };
+            match change {
+                NbtChange::Added { value, .. } | NbtChange::Removed { value, .. } => tags.push(named("value", value)),
+                NbtChange::Changed { old, new, .. } | NbtChange::TypeChanged { old, new, .. } => {
+                    tags.push(named("old", old));
+                    tags.push(named("new", new));
+                }
+            }
+            NbtCompound::from(tags)
+        }).collect::<Vec<_>>();
+        let list = if changes.is_empty() { NbtList::default() } else { NbtList::from(changes) };
+        NbtTag::List(name.into(), list)
+    }
+
+    /// Reads a patch written by [`NbtDiff::to_tag`].
+    pub fn from_tag(tag: &NbtTag) -> Result<NbtDiff, NbtParseError> {
+        let list = tag.as_list().ok_or_else(|| NbtParseError::WrongType("list".to_string(), tag.type_name()))?;
+        let changes = list.iter().map(|change| {
+            let field = |name: &str| change.get(name).ok_or_else(|| NbtParseError::NoSuchValue(name.to_string()));
+            let string = |name: &str| {
+                let tag = field(name)?;
+                tag.as_str().ok_or_else(|| NbtParseError::WrongType("string".to_string(), tag.type_name()))
+            };
+            let path = match string("path")? {
+                "" => NbtPath::root(),
+                path => path.parse()?,
+            };
+            let value = |name: &str| field(name).map(unnamed);
+            Ok(match string("op")? {
+                "add" => NbtChange::Added { path, value: value("value")? },
+                "remove" => NbtChange::Removed { path, value: value("value")? },
+                "change" => NbtChange::Changed { path, old: value("old")?, new: value("new")? },
+                "type_change" => NbtChange::TypeChanged { path, old: value("old")?, new: value("new")? },
+                op => return Err(InvalidValue(format!("unknown patch operation {op}"))),
+            })
+        }).collect::<Result<_, _>>()?;
+        Ok(NbtDiff { changes })
+    }
+
+    /// Compares two tags, which are only End at the root.
+    fn compare_tags(&mut self, path: NbtPath, old: &NbtTag, new: &NbtTag, options: &DiffOptions) {
+        match (old.as_value(), new.as_value()) {
+            (Some(old), Some(new)) => self.compare(path, old, new, options),
+            _ if old.type_id() != new.type_id() => {
+                self.changes.push(NbtChange::TypeChanged { path, old: unnamed(old), new: unnamed(new) });
+            }
+            _ => {}
+        }
+    }
+
+    fn compare(&mut self, path: NbtPath, old: NbtValue<'_>, new: NbtValue<'_>, options: &DiffOptions) {
+        if unchanged(old, new) {
+            return;
+        }
+        match (old, new) {
+            (NbtValue::Compound(old_tags), NbtValue::Compound(new_tags)) => {
+                for old_tag in old_tags {
+                    let child = path.child(old_tag.name());
+                    match new_tags.get(old_tag.name()) {
+                        Some(new_tag) => self.compare_tags(child, old_tag, new_tag, options),
+                        None => self.changes.push(NbtChange::Removed { path: child, value: unnamed(old_tag) }),
+                    }
+                }
+                for new_tag in new_tags.iter().filter(|tag| !old_tags.contains_key(tag.name())) {
+                    self.changes.push(NbtChange::Added { path: path.child(new_tag.name()), value: unnamed(new_tag) });
+                }
+            }
+            (NbtValue::List(old_list), NbtValue::List(new_list)) if old_list.element_type() == new_list.element_type() => {
+                match list_key(old_list, new_list, options) {
+                    Some(key) if keeps_order(old_list, new_list, key) => self.compare_by_key(path, key, old_list, new_list, options),
+                    _ => self.compare_by_index(path, old_list, new_list, options),
+                }
+            }
+            _ if old.type_id() != new.type_id() => {
+                self.changes.push(NbtChange::TypeChanged { path, old: old.to_tag(""), new: new.to_tag("") });
+            }
+            _ => self.changes.push(NbtChange::Changed { path, old: old.to_tag(""), new: new.to_tag("") }),
+        }
+    }
+
+    /// Compares elements at the same index. Surplus old elements are removed from the back,
+    /// so the changes can be applied in order.
+    fn compare_by_index(&mut self, path: NbtPath, old: &NbtList, new: &NbtList, options: &DiffOptions) {
+        for (index, (old_value, new_value)) in old.iter().zip(new.iter()).enumerate() {
+            self.compare(path.index(index as i32), old_value, new_value, options);
+        }
+        for (index, value) in old.iter().enumerate().skip(new.len()).rev() {
+            self.changes.push(NbtChange::Removed { path: path.index(index as i32), value: value.to_tag("") });
+        }
+        for (index, value) in new.iter().enumerate().skip(old.len()) {
+            self.changes.push(NbtChange::Added { path: path.index(index as i32), value: value.to_tag("") });
+        }
+    }
+
+    fn compare_by_key(&mut self, path: NbtPath, key: &str, old: &NbtList, new: &NbtList, options: &DiffOptions) {
+        let new_by_key = new.iter().map(|value| (key_value(value, key), value)).collect::<HashMap<_, _>>();
+        let mut seen = HashSet::new();
+        for old_value in old {
+            let value = key_value(old_value, key);
+            let element = path.filtered(vec![old_value.get(key).unwrap().clone()].into());
+            match new_by_key.get(&value) {
+                Some(new_value) => self.compare(element, old_value, *new_value, options),
+                None => self.changes.push(NbtChange::Removed { path: element, value: old_value.to_tag("") }),
+            }
+            seen.insert(value);
+        }
+        for new_value in new.iter().filter(|value| !seen.contains(&key_value(*value, key))) {
+            let element = path.filtered(vec![new_value.get(key).unwrap().clone()].into());
+            self.changes.push(NbtChange::Added { path: element, value: new_value.to_tag("") });
+        }
+    }
+}
+
+impl Display for NbtDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl NbtTag {
+    /// Compares this tree with a newer version of it. See [`NbtDiff`].
+    pub fn diff(&self, new: &NbtTag) -> NbtDiff {
+        NbtDiff::new(self, new)
+    }
+}
+
+fn unnamed(tag: &NbtTag) -> NbtTag {
+    let mut tag = tag.clone();
+    tag.set_name("");
+    tag
+}
+
+/// Whether two values are equal, comparing floats by their bits so NaN equals itself.
+/// Lists and compounds are compared element by element instead.
+fn unchanged(old: NbtValue<'_>, new: NbtValue<'_>) -> bool {
+    match (old, new) {
+        (NbtValue::Float(old), NbtValue::Float(new)) => old.to_bits() == new.to_bits(),
+        (NbtValue::Double(old), NbtValue::Double(new)) => old.to_bits() == new.to_bits(),
+        (NbtValue::List(_), NbtValue::List(_)) | (NbtValue::Compound(_), NbtValue::Compound(_)) => false,
+        (old, new) => old == new,
+    }
+}
+
+/// Whether applying a diff by key gives the order of `new`: elements in both lists keep
+/// their order and added elements come last, as they are appended.
+fn keeps_order(old: &NbtList, new: &NbtList, key: &str) -> bool {
+    let keys = |list: &NbtList| list.iter().map(|value| key_value(value, key)).collect::<Vec<_>>();
+    let (old_keys, new_keys) = (keys(old), keys(new));
+    let kept = old_keys.iter().filter(|key| new_keys.contains(key));
+    let added = new_keys.iter().filter(|key| !old_keys.contains(key));
+    kept.chain(added).eq(new_keys.iter())
+}
+
+/// Identifies the key field of a list element, as SNBT so it can be hashed.
+fn key_value(value: NbtValue<'_>, key: &str) -> String {
+    value.get(key).map(to_snbt).unwrap_or_default()
+}
+
+/// Finds the first key field that all elements of both lists have, with unique values.
+fn list_key<'a>(old: &NbtList, new: &NbtList, options: &'a DiffOptions) -> Option<&'a str> {
+    options.list_keys.iter().map(|key| key.as_str()).find(|key| {
+        [old, new].into_iter().all(|list| {
+            let mut values = HashSet::new();
+            list.iter().all(|value| value.get(key).is_some() && values.insert(key_value(value, key)))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snbt_parser::parse_snbt;
+
+    #[test]
+    fn diff_compounds() {
+        let old = parse_snbt(r#"{Time:1L,Name:"a",Gone:1b,Pos:[1.0d,2.0d,3.0d],Mode:1}"#).unwrap();
+        let new = parse_snbt(r#"{Time:2L,Name:"a",Pos:[1.0d,2.5d],Mode:"creative",New:{}}"#).unwrap();
+        let diff = old.diff(&new);
+        assert_eq!(diff.to_string(), [
+            "~ Time: 1L -> 2L",
+            "- Gone: 1b",
+            "~ Pos[1]: 2.0d -> 2.5d",
+            "- Pos[2]: 3.0d",
+            "! Mode: int 1 -> string \"creative\"",
+            "+ New: {}",
+            "",
+        ].join("\n"));
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(NbtDiff::from_tag(&diff.to_tag("patch")).unwrap(), diff);
+    }
+
+    #[test]
+    fn diff_by_key() {
+        let old = parse_snbt(r#"{Inventory:[{Slot:0b,id:"stone",count:1},{Slot:1b,id:"dirt",count:1}]}"#).unwrap();
+        let new = parse_snbt(r#"{Inventory:[{Slot:1b,id:"dirt",count:2},{Slot:2b,id:"sand",count:1}]}"#).unwrap();
+        let options = DiffOptions::new().with_list_key("UUID").with_list_key("Slot");
+        assert_eq!(NbtDiff::with_options(&old, &new, &options).to_string(), [
+            "- Inventory[{Slot:0b}]: {Slot:0b,id:\"stone\",count:1}",
+            "~ Inventory[{Slot:1b}].count: 1 -> 2",
+            "+ Inventory[{Slot:2b}]: {Slot:2b,id:\"sand\",count:1}",
+            "",
+        ].join("\n"));
+        assert_eq!(NbtDiff::new(&old, &new).changes.len(), 5);
+
+        let reordered = parse_snbt(r#"{Inventory:[{Slot:1b,id:"dirt",count:1},{Slot:0b,id:"stone",count:1}]}"#).unwrap();
+        let diff = NbtDiff::with_options(&old, &reordered, &options);
+        assert!(!diff.is_empty());
+        let mut patched = old.clone();
+        patched.apply_patch(&diff).unwrap();
+        assert_eq!(patched, reordered);
+    }
+
+    #[test]
+    fn diff_floats() {
+        let old = parse_snbt("{a:NaNf,b:[NaNd],c:0.0d}").unwrap();
+        assert!(old.diff(&old.clone()).is_empty());
+        let new = parse_snbt("{a:NaNf,b:[NaNd],c:-0.0d}").unwrap();
+        assert_eq!(old.diff(&new).to_string(), "~ c: 0.0d -> -0.0d\n");
+    }
+}
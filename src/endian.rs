@@ -0,0 +1,140 @@
+use crate::NbtParseError;
+
+/// Conversion between big-endian NBT, used by Java Edition, and little-endian NBT, used by
+/// Bedrock Edition. The data is walked structurally, so only numbers and lengths are swapped.
+pub mod nbt_endian {
+    use super::*;
+
+    /// Converts little-endian binary NBT to big-endian.
+    pub fn little_to_big(data: &[u8]) -> Result<Vec<u8>, NbtParseError> {
+        swap(data, true)
+    }
+
+    /// Converts big-endian binary NBT to little-endian.
+    pub fn big_to_little(data: &[u8]) -> Result<Vec<u8>, NbtParseError> {
+        swap(data, false)
+    }
+
+    fn swap(data: &[u8], from_little: bool) -> Result<Vec<u8>, NbtParseError> {
+        let mut swapper = Swapper { data, pos: 0, from_little, out: Vec::with_capacity(data.len()) };
+        swapper.named()?;
+        Ok(swapper.out)
+    }
+}
+
+struct Swapper<'a> {
+    data: &'a [u8],
+    pos: usize,
+    from_little: bool,
+    out: Vec<u8>,
+}
+
+impl Swapper<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], NbtParseError> {
+        let bytes = self.data.get(self.pos..self.pos + len).ok_or(NbtParseError::EndOfData)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn copy(&mut self, len: usize) -> Result<(), NbtParseError> {
+        let bytes = self.data.get(self.pos..self.pos + len).ok_or(NbtParseError::EndOfData)?;
+        self.out.extend_from_slice(bytes);
+        self.pos += len;
+        Ok(())
+    }
+
+    /// Copies a number of `size` bytes with its byte order reversed.
+    fn number(&mut self, size: usize) -> Result<(), NbtParseError> {
+        let bytes = self.data.get(self.pos..self.pos + size).ok_or(NbtParseError::EndOfData)?;
+        self.out.extend(bytes.iter().rev());
+        self.pos += size;
+        Ok(())
+    }
+
+    fn type_id(&mut self) -> Result<i8, NbtParseError> {
+        let id = self.take(1)?[0] as i8;
+        self.out.push(id as u8);
+        Ok(id)
+    }
+
+    fn length(&mut self) -> Result<usize, NbtParseError> {
+        let from_little = self.from_little;
+        let bytes: [u8; 4] = self.take(4)?.try_into().unwrap();
+        let len = if from_little { i32::from_le_bytes(bytes) } else { i32::from_be_bytes(bytes) };
+        self.out.extend(bytes.iter().rev());
+        Ok(len.max(0) as usize)
+    }
+
+    fn string(&mut self) -> Result<(), NbtParseError> {
+        let from_little = self.from_little;
+        let bytes: [u8; 2] = self.take(2)?.try_into().unwrap();
+        let len = if from_little { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) };
+        self.out.extend(bytes.iter().rev());
+        self.copy(len as usize)
+    }
+
+    fn named(&mut self) -> Result<i8, NbtParseError> {
+        let id = self.type_id()?;
+        if id != 0 {
+            self.string()?;
+            self.payload(id)?;
+        }
+        Ok(id)
+    }
+
+    fn payload(&mut self, id: i8) -> Result<(), NbtParseError> {
+        match id {
+            1 => self.copy(1),
+            2 => self.number(2),
+            3 | 5 => self.number(4),
+            4 | 6 => self.number(8),
+            7 => {
+                let len = self.length()?;
+                self.copy(len)
+            }
+            8 => self.string(),
+            9 => {
+                let element_type = self.type_id()?;
+                let len = self.length()?;
+                for _ in 0..len {
+                    self.payload(element_type)?;
+                }
+                Ok(())
+            }
+            10 => {
+                while self.named()? != 0 {}
+                Ok(())
+            }
+            11 => {
+                let len = self.length()?;
+                (0..len).try_for_each(|_| self.number(4))
+            }
+            12 => {
+                let len = self.length()?;
+                (0..len).try_for_each(|_| self.number(8))
+            }
+            id => Err(NbtParseError::UnknownNBT(id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::nbt_endian::*;
+    use crate::{nbt_parser, nbt_writer, NbtTag};
+    use crate::snbt_parser::parse_snbt;
+
+    #[test]
+    fn swap_round_trip() {
+        let mut tag = parse_snbt(r#"{s:1s,i:2,l:3L,f:1.5f,d:2.5d,b:[B;1b,2b],str:"ab",list:[[1s,2s],[]],c:{x:1},ia:[I;1,-1],la:[L;5L]}"#).unwrap();
+        tag.set_name("root");
+        let big = nbt_writer::write_binary(&tag).unwrap();
+        let little = big_to_little(&big).unwrap();
+        assert_eq!(&little[..7], &[10, 4, 0, b'r', b'o', b'o', b't']);
+        assert_eq!(little_to_big(&little).unwrap(), big);
+        assert_eq!(nbt_parser::parse_binary_le(little.clone()).unwrap(), tag);
+        assert_eq!(nbt_writer::write_binary_le(&tag).unwrap(), little);
+        assert!(little_to_big(&little[..little.len() - 1]).is_err());
+        assert_eq!(nbt_parser::parse_binary_le(vec![3, 1, 0, b'a', 1, 0, 0, 0]).unwrap(), NbtTag::Int("a".to_string(), 1));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::NbtParseError;
+use crate::NbtParseError::{InvalidValue, WrongLength, WrongType};
+use crate::types::NbtTag;
+
+/// Number of columns in a chunk, and therefore entries in a heightmap.
+pub const HEIGHTMAP_SIZE: usize = 16 * 16;
+
+/// The different heightmaps a chunk can store in its `Heightmaps` compound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeightmapKind {
+    WorldSurfaceWg,
+    WorldSurface,
+    OceanFloorWg,
+    OceanFloor,
+    MotionBlocking,
+    MotionBlockingNoLeaves,
+}
+
+impl HeightmapKind {
+    pub const ALL: [HeightmapKind; 6] = [
+        HeightmapKind::WorldSurfaceWg,
+        HeightmapKind::WorldSurface,
+        HeightmapKind::OceanFloorWg,
+        HeightmapKind::OceanFloor,
+        HeightmapKind::MotionBlocking,
+        HeightmapKind::MotionBlockingNoLeaves,
+    ];
+
+    /// The name of the heightmap inside the `Heightmaps` compound.
+    pub fn nbt_name(&self) -> &'static str {
+        match self {
+            HeightmapKind::WorldSurfaceWg => "WORLD_SURFACE_WG",
+            HeightmapKind::WorldSurface => "WORLD_SURFACE",
+            HeightmapKind::OceanFloorWg => "OCEAN_FLOOR_WG",
+            HeightmapKind::OceanFloor => "OCEAN_FLOOR",
+            HeightmapKind::MotionBlocking => "MOTION_BLOCKING",
+            HeightmapKind::MotionBlockingNoLeaves => "MOTION_BLOCKING_NO_LEAVES",
+        }
+    }
+}
+
+/// A decoded heightmap, containing the absolute Y value of every column in a chunk.
+/// The Y value is the first free block above the highest block matching the heightmap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmap {
+    heights: Vec<i32>,
+}
+
+impl Heightmap {
+    /// Creates a heightmap where every column has the given height.
+    pub fn filled(height: i32) -> Heightmap {
+        Heightmap { heights: vec![height; HEIGHTMAP_SIZE] }
+    }
+
+    /// Number of bits used per entry for a world of the given height.
+    pub fn bits_per_entry(world_height: u32) -> u32 {
+        u32::BITS - world_height.leading_zeros()
+    }
+
+    /// Number of longs needed to store a packed heightmap for a world of the given height.
+    pub fn packed_len(world_height: u32) -> usize {
+        let per_long = (64 / Self::bits_per_entry(world_height)) as usize;
+        HEIGHTMAP_SIZE.div_ceil(per_long)
+    }
+
+    /// Decodes a packed heightmap. Entries never span two longs, and the stored values are
+    /// relative to `min_y`, the lowest block of the world.
+    pub fn from_packed(data: &[i64], min_y: i32, world_height: u32) -> Result<Heightmap, NbtParseError> {
+        let expected = Self::packed_len(world_height);
+        if data.len() != expected {
+            return Err(WrongLength(expected, data.len()));
+        }
+        let bits = Self::bits_per_entry(world_height);
+        let per_long = (64 / bits) as usize;
+        let mask = (1u64 << bits) - 1;
+        let heights = (0..HEIGHTMAP_SIZE).map(|i| {
+            let long = data[i / per_long] as u64;
+            let value = (long >> ((i % per_long) as u32 * bits)) & mask;
+            value as i32 + min_y
+        }).collect();
+        Ok(Heightmap { heights })
+    }
+
+    /// Packs the heightmap back into longs, the inverse of [`Heightmap::from_packed`].
+    pub fn to_packed(&self, min_y: i32, world_height: u32) -> Result<Vec<i64>, NbtParseError> {
+        let bits = Self::bits_per_entry(world_height);
+        let per_long = (64 / bits) as usize;
+        let mut data = vec![0u64; Self::packed_len(world_height)];
+        for (i, height) in self.heights.iter().enumerate() {
+            let relative = height - min_y;
+            if relative < 0 || relative as u32 > world_height {
+                return Err(InvalidValue(format!("height {height} is outside of the world")));
+            }
+            data[i / per_long] |= (relative as u64) << ((i % per_long) as u32 * bits);
+        }
+        Ok(data.into_iter().map(|n| n as i64).collect())
+    }
+
+    /// Absolute Y value of the column at the given chunk local coordinates.
+    pub fn get(&self, x: usize, z: usize) -> i32 {
+        self.heights[Self::index(x, z)]
+    }
+
+    pub fn set(&mut self, x: usize, z: usize, height: i32) {
+        self.heights[Self::index(x, z)] = height;
+    }
+
+    /// All heights, indexed by `z * 16 + x`.
+    pub fn heights(&self) -> &[i32] {
+        &self.heights
+    }
+
+    /// The heights as a grid, indexed as `grid[z][x]`.
+    pub fn to_grid(&self) -> [[i32; 16]; 16] {
+        let mut grid = [[0; 16]; 16];
+        for (z, row) in grid.iter_mut().enumerate() {
+            row.copy_from_slice(&self.heights[z * 16..z * 16 + 16]);
+        }
+        grid
+    }
+
+    fn index(x: usize, z: usize) -> usize {
+        assert!(x < 16 && z < 16, "Column ({x}, {z}) is outside of the chunk");
+        z * 16 + x
+    }
+}
+
+impl NbtTag {
+    /// Returns the lowest section of a chunk, `yPos` in 1.18+ chunks. Older chunks always
+    /// start at section 0.
+    pub fn get_min_section(&self) -> Result<i32, NbtParseError> {
+        match self.get_int("yPos") {
+            Err(NbtParseError::NoSuchValue(_)) => Ok(0),
+            result => result,
+        }
+    }
+
+    /// Decodes one of the heightmaps of a chunk. Both the 1.18+ layout and the older layout,
+    /// where everything lives in the `Level` compound, are supported.
+    pub fn get_heightmap(&self, kind: HeightmapKind, world_height: u32) -> Result<Heightmap, NbtParseError> {
+        let chunk = match self.get_ref("Level") {
+            Ok(level) => level,
+            Err(NbtParseError::NoSuchValue(_)) => self,
+            Err(e) => return Err(e),
+        };
+        let data = chunk.get_ref("Heightmaps")?.get_ref(kind.nbt_name())?;
+        let data = data.as_long_array().ok_or_else(|| WrongType("long array".to_string(), data.type_name()))?;
+        Heightmap::from_packed(data, chunk.get_min_section()? * 16, world_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_len() {
+        assert_eq!(Heightmap::bits_per_entry(384), 9);
+        assert_eq!(Heightmap::bits_per_entry(256), 9);
+        assert_eq!(Heightmap::bits_per_entry(4064), 12);
+        assert_eq!(Heightmap::packed_len(384), 37);
+        assert_eq!(Heightmap::packed_len(4064), 52);
+    }
+
+    #[test]
+    fn decode_chunk_heightmap() {
+        let mut heightmap = Heightmap::filled(-64);
+        heightmap.set(0, 0, 70);
+        heightmap.set(15, 15, 319);
+        heightmap.set(3, 7, 0);
+        let packed = heightmap.to_packed(-64, 384).unwrap();
+        assert_eq!(packed[0] & 0x1ff, 134);
+
+        let chunk = NbtTag::Compound("".to_string(), vec![
+            NbtTag::Int("yPos".to_string(), -4),
+            NbtTag::Compound("Heightmaps".to_string(), vec![
+                NbtTag::LongArray("WORLD_SURFACE".to_string(), packed),
+            ].into()),
+        ].into());
+        let decoded = chunk.get_heightmap(HeightmapKind::WorldSurface, 384).unwrap();
+        assert_eq!(decoded, heightmap);
+        assert_eq!(decoded.to_grid()[7][3], 0);
+        assert!(chunk.get_heightmap(HeightmapKind::OceanFloor, 384).is_err());
+        assert!(matches!(chunk.get_heightmap(HeightmapKind::WorldSurface, 4064), Err(WrongLength(52, 37))));
+    }
+}
@@ -0,0 +1,593 @@
+use crate::NbtParseError;
+use crate::NbtParseError::{NoSuchValue, WrongType};
+use crate::model::{compound, field, key_order, optional, ordered_compound, unknown_fields};
+use crate::compound::NbtCompound;
+use crate::list::NbtList;
+use crate::types::NbtTag;
+use crate::value::NbtValue;
+
+/// The layouts item stacks have been saved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemFormat {
+    /// `{id, Count:b, Damage:s, tag:{...}}` with numeric enchantment ids, used before 1.13.
+    PreFlattening,
+    /// `{id, Count:b, tag:{...}}`, used before 1.20.5.
+    Legacy,
+    /// `{id, count:i, components:{...}}`, used since 1.20.5.
+    Components,
+}
+
+impl ItemFormat {
+    /// Name of the compound holding the extra item data.
+    pub fn data_name(&self) -> &'static str {
+        match self {
+            ItemFormat::PreFlattening | ItemFormat::Legacy => "tag",
+            ItemFormat::Components => "components",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Enchantment {
+    pub id: String,
+    pub level: i32,
+}
+
+/// A stack of items, read from either item layout. The common fields are typed, the rest of
+/// `tag` or `components` is kept in `extra`, and unknown fields next to `id` are kept in `other`.
+///
+/// Converting between the `tag` and `components` layouts translates the typed fields and the
+/// entries of `extra` that have a known equivalent, see [`ItemStack::convert`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStack {
+    pub id: String,
+    pub count: i32,
+    /// The custom name as a JSON text component.
+    pub custom_name: Option<String>,
+    pub enchantments: Vec<Enchantment>,
+    pub damage: Option<i32>,
+    pub extra: Vec<NbtTag>,
+    pub other: Vec<NbtTag>,
+    pub format: ItemFormat,
+    /// Fields of `minecraft:enchantments` other than `levels`, like `show_in_tooltip`.
+    enchantment_fields: Vec<NbtTag>,
+    /// Whether `minecraft:enchantments` holds the levels directly, as since 1.21.5.
+    flat_enchantments: bool,
+    key_order: Vec<String>,
+    extra_key_order: Vec<String>,
+}
+
+impl ItemStack {
+    const KNOWN: [&'static str; 5] = ["id", "count", "Count", "tag", "components"];
+
+    pub fn new<S: Into<String>>(id: S, count: i32) -> ItemStack {
+        ItemStack {
+            id: id.into(),
+            count,
+            custom_name: None,
+            enchantments: vec![],
+            damage: None,
+            extra: vec![],
+            other: vec![],
+            format: ItemFormat::Components,
+            enchantment_fields: vec![],
+            flat_enchantments: false,
+            key_order: vec![],
+            extra_key_order: vec![],
+        }
+    }
+
+    pub fn from_tag(tag: &NbtTag) -> Result<ItemStack, NbtParseError> {
+        ItemStack::from_compound(compound(tag)?)
+    }
+
+    /// Reads a stack from the children of its compound, like an element of an item list.
+    pub fn from_compound(tags: &NbtCompound) -> Result<ItemStack, NbtParseError> {
+        let (count, format) = match optional(field(tags, "count", "int", NbtTag::as_i32))? {
+            Some(count) => (count, ItemFormat::Components),
+            None => match optional(field(tags, "Count", "byte", NbtTag::as_i8))? {
+                Some(count) if matches!(tags.get("Damage"), Some(NbtTag::Short(_, _))) => (count as i32, ItemFormat::PreFlattening),
+                Some(count) => (count as i32, ItemFormat::Legacy),
+                None if tags.contains_key("tag") => (1, ItemFormat::Legacy),
+                None => (1, ItemFormat::Components),
+            },
+        };
+        let mut item = ItemStack { format, ..ItemStack::new(field(tags, "id", "string", NbtTag::as_str)?, count) };
+        item.other = unknown_fields(tags, &Self::KNOWN);
+        item.key_order = key_order(tags);
+        if format == ItemFormat::PreFlattening {
+            item.damage = Some(field(tags, "Damage", "short", NbtTag::as_i16)? as i32);
+            item.other.retain(|tag| tag.name() != "Damage");
+        }
+        if let Some(data) = optional(field(tags, format.data_name(), "compound", NbtTag::as_compound))? {
+            item.extra_key_order = key_order(data);
+            item.extra = unknown_fields(data, &[]);
+            match format {
+                ItemFormat::PreFlattening | ItemFormat::Legacy => item.read_legacy_data()?,
+                ItemFormat::Components => item.read_components()?,
+            }
+        }
+        Ok(item)
+    }
+
+    /// Converts the stack into a compound in its current `format`.
+    pub fn to_tag<S: Into<String>>(&self, name: S) -> NbtTag {
+        self.to_tag_with(&name.into(), self.format, vec![])
+    }
+
+    /// Converts the stack into a compound in the given layout. Entries of `extra` without an
+    /// equivalent in that layout are left out, [`ItemStack::convert`] returns them instead.
+    pub fn to_tag_as<S: Into<String>>(&self, name: S, format: ItemFormat) -> NbtTag {
+        let mut item = self.clone();
+        item.convert(format);
+        item.to_tag_with(&name.into(), format, vec![])
+    }
+
+    /// Switches the stack to another layout. When moving between `tag` and `components`, the
+    /// entries of `extra` with a known equivalent are translated, and the ones without are
+    /// removed and returned.
+    pub fn convert(&mut self, format: ItemFormat) -> Vec<NbtTag> {
+        let mut untranslated = vec![];
+        let components = format == ItemFormat::Components;
+        if components != (self.format == ItemFormat::Components) {
+            let extra = std::mem::take(&mut self.extra);
+            self.extra = if components {
+                components_from_legacy(extra, &mut untranslated)
+            } else {
+                legacy_from_components(extra, &mut untranslated)
+            };
+            self.extra_key_order.clear();
+            if !self.enchantment_fields.is_empty() {
+                let fields = std::mem::take(&mut self.enchantment_fields);
+                untranslated.push(NbtTag::Compound("minecraft:enchantments".to_string(), fields.into()));
+            }
+        }
+        self.format = format;
+        untranslated
+    }
+
+    /// Converts the stack into a compound, together with extra fields like `Slot`.
+    pub(crate) fn to_tag_with(&self, name: &str, format: ItemFormat, mut tags: Vec<NbtTag>) -> NbtTag {
+        tags.push(NbtTag::String("id".to_string(), self.id.clone()));
+        let data = match format {
+            ItemFormat::PreFlattening => {
+                tags.push(NbtTag::Byte("Count".to_string(), saturate(self.count, i8::MIN, i8::MAX)));
+                tags.push(NbtTag::Short("Damage".to_string(), saturate(self.damage.unwrap_or(0), i16::MIN, i16::MAX)));
+                self.legacy_data(true)
+            }
+            ItemFormat::Legacy => {
+                tags.push(NbtTag::Byte("Count".to_string(), saturate(self.count, i8::MIN, i8::MAX)));
+                self.legacy_data(false)
+            }
+            ItemFormat::Components => {
+                tags.push(NbtTag::Int("count".to_string(), self.count));
+                self.components()
+            }
+        };
+        if !data.is_empty() || self.key_order.iter().any(|key| key == format.data_name()) {
+            tags.push(ordered_compound(format.data_name(), &self.extra_key_order, data));
+        }
+        tags.extend(self.other.iter().cloned());
+        ordered_compound(name, &self.key_order, tags)
+    }
+
+    fn take_extra(&mut self, name: &str) -> Option<NbtTag> {
+        let index = self.extra.iter().position(|tag| tag.name() == name)?;
+        Some(self.extra.remove(index))
+    }
+
+    fn read_legacy_data(&mut self) -> Result<(), NbtParseError> {
+        if self.format == ItemFormat::Legacy {
+            match self.take_extra("Damage") {
+                Some(NbtTag::Int(_, damage)) => self.damage = Some(damage),
+                Some(tag) => self.extra.push(tag),
+                None => {}
+            }
+        }
+        if let Some(NbtTag::Compound(name, mut display)) = self.take_extra("display") {
+            if let Some(NbtTag::String(_, custom_name)) = display.get("Name") {
+                self.custom_name = Some(custom_name.clone());
+                display.remove("Name");
+            }
+            if !display.is_empty() {
+                self.extra.push(NbtTag::Compound(name, display));
+            }
+        }
+        // Before 1.13 the list was called `ench`
+        for name in ["Enchantments", "ench"] {
+            match self.take_extra(name) {
+                Some(NbtTag::List(name, list)) => match legacy_enchantments(&list)? {
+                    Some(enchantments) => self.enchantments.extend(enchantments),
+                    None => self.extra.push(NbtTag::List(name, list)),
+                },
+                Some(tag) => self.extra.push(tag),
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn read_components(&mut self) -> Result<(), NbtParseError> {
+        match self.take_extra("minecraft:damage") {
+            Some(NbtTag::Int(_, damage)) => self.damage = Some(damage),
+            Some(tag) => self.extra.push(tag),
+            None => {}
+        }
+        match self.take_extra("minecraft:custom_name") {
+            Some(NbtTag::String(_, custom_name)) => self.custom_name = Some(custom_name),
+            Some(tag) => self.extra.push(tag),
+            None => {}
+        }
+        match self.take_extra("minecraft:enchantments") {
+            Some(NbtTag::Compound(_, fields)) => {
+                // Since 1.21.5 the levels are stored directly in the component
+                let levels = match fields.iter().find(|tag| tag.name() == "levels") {
+                    Some(NbtTag::Compound(_, levels)) => {
+                        self.enchantment_fields = fields.iter().filter(|tag| tag.name() != "levels").cloned().collect();
+                        levels.clone().into_vec()
+                    }
+                    _ => {
+                        self.flat_enchantments = true;
+                        fields.into_vec()
+                    }
+                };
+                for level in levels {
+                    match level {
+                        NbtTag::Int(id, level) => self.enchantments.push(Enchantment { id, level }),
+                        tag => return Err(WrongType("int".to_string(), tag.type_name())),
+                    }
+                }
+            }
+            Some(tag) => self.extra.push(tag),
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// The contents of `tag`, with numeric enchantment ids in `ench` if `numeric` is true,
+    /// in which case the damage is written next to `tag`.
+    fn legacy_data(&self, numeric: bool) -> Vec<NbtTag> {
+        let mut data = self.extra.clone();
+        if let Some(damage) = self.damage.filter(|_| !numeric) {
+            data.push(NbtTag::Int("Damage".to_string(), damage));
+        }
+        if let Some(custom_name) = &self.custom_name {
+            let name = NbtTag::String("Name".to_string(), custom_name.clone());
+            match data.iter_mut().find(|tag| tag.name() == "display") {
+                Some(NbtTag::Compound(_, display)) => {
+                    *display = std::iter::once(name).chain(display.iter().cloned()).collect();
+                }
+                _ => data.push(NbtTag::Compound("display".to_string(), vec![name].into())),
+            }
+        }
+        if !self.enchantments.is_empty() {
+            let enchantments = self.enchantments.iter().map(|enchantment| {
+                let id = match enchantment_number(&enchantment.id).filter(|_| numeric) {
+                    Some(id) => NbtTag::Short("id".to_string(), id),
+                    None => NbtTag::String("id".to_string(), enchantment.id.clone()),
+                };
+                NbtCompound::from(vec![id, NbtTag::Short("lvl".to_string(), saturate(enchantment.level, i16::MIN, i16::MAX))])
+            }).collect::<Vec<_>>();
+            let name = if numeric { "ench" } else { "Enchantments" };
+            data.push(NbtTag::List(name.to_string(), enchantments.into()));
+        }
+        data
+    }
+
+    fn components(&self) -> Vec<NbtTag> {
+        let mut data = self.extra.clone();
+        if let Some(damage) = self.damage {
+            data.push(NbtTag::Int("minecraft:damage".to_string(), damage));
+        }
+        if let Some(custom_name) = &self.custom_name {
+            data.push(NbtTag::String("minecraft:custom_name".to_string(), custom_name.clone()));
+        }
+        if !self.enchantments.is_empty() {
+            let levels = self.enchantments.iter()
+                .map(|enchantment| NbtTag::Int(enchantment.id.clone(), enchantment.level))
+                .collect();
+            let enchantments = if self.flat_enchantments {
+                levels
+            } else {
+                let mut fields = vec![NbtTag::Compound("levels".to_string(), levels)];
+                fields.extend(self.enchantment_fields.iter().cloned());
+                fields.into()
+            };
+            data.push(NbtTag::Compound("minecraft:enchantments".to_string(), enchantments));
+        }
+        data
+    }
+}
+
+/// Narrows a count, damage or level to a smaller legacy field, clamping values that don't fit.
+fn saturate<T: TryFrom<i32>>(value: i32, min: T, max: T) -> T {
+    T::try_from(value).unwrap_or(if value < 0 { min } else { max })
+}
+
+/// The numeric enchantment ids used before 1.13.
+const ENCHANTMENT_IDS: [(i16, &str); 30] = [
+    (0, "minecraft:protection"), (1, "minecraft:fire_protection"), (2, "minecraft:feather_falling"),
+    (3, "minecraft:blast_protection"), (4, "minecraft:projectile_protection"), (5, "minecraft:respiration"),
+    (6, "minecraft:aqua_affinity"), (7, "minecraft:thorns"), (8, "minecraft:depth_strider"),
+    (9, "minecraft:frost_walker"), (10, "minecraft:binding_curse"), (16, "minecraft:sharpness"),
+    (17, "minecraft:smite"), (18, "minecraft:bane_of_arthropods"), (19, "minecraft:knockback"),
+    (20, "minecraft:fire_aspect"), (21, "minecraft:looting"), (22, "minecraft:sweeping"),
+    (32, "minecraft:efficiency"), (33, "minecraft:silk_touch"), (34, "minecraft:unbreaking"),
+    (35, "minecraft:fortune"), (48, "minecraft:power"), (49, "minecraft:punch"), (50, "minecraft:flame"),
+    (51, "minecraft:infinity"), (61, "minecraft:luck_of_the_sea"), (62, "minecraft:lure"),
+    (70, "minecraft:mending"), (71, "minecraft:vanishing_curse"),
+];
+
+fn enchantment_name(id: i16) -> Option<&'static str> {
+    ENCHANTMENT_IDS.iter().find(|(number, _)| *number == id).map(|(_, name)| *name)
+}
+
+fn enchantment_number(name: &str) -> Option<i16> {
+    ENCHANTMENT_IDS.iter().find(|(_, id)| *id == name).map(|(number, _)| *number)
+}
+
+/// Reads an `Enchantments` or `ench` list. Lists with numeric ids that have no name, like
+/// those of mods, are not read.
+fn legacy_enchantments(list: &NbtList) -> Result<Option<Vec<Enchantment>>, NbtParseError> {
+    let mut enchantments = vec![];
+    for enchantment in list {
+        let field = |name: &str| enchantment.get(name).ok_or_else(|| NoSuchValue(name.to_string()));
+        let level = match field("lvl")? {
+            NbtTag::Short(_, level) => *level as i32,
+            NbtTag::Int(_, level) => *level,
+            tag => return Err(WrongType("short".to_string(), tag.type_name())),
+        };
+        let id = match field("id")? {
+            NbtTag::String(_, id) => id.clone(),
+            NbtTag::Short(_, id) => match enchantment_name(*id) {
+                Some(name) => name.to_string(),
+                None => return Ok(None),
+            },
+            tag => return Err(WrongType("string".to_string(), tag.type_name())),
+        };
+        enchantments.push(Enchantment { id, level });
+    }
+    Ok(Some(enchantments))
+}
+
+/// Entries of `tag` holding the same int as a component.
+const RENAMED_ENTRIES: [(&str, &str); 2] = [
+    ("RepairCost", "minecraft:repair_cost"), ("CustomModelData", "minecraft:custom_model_data"),
+];
+
+/// Translates the remaining entries of `tag` into components, moving the ones without an
+/// equivalent to `untranslated`.
+fn components_from_legacy(extra: Vec<NbtTag>, untranslated: &mut Vec<NbtTag>) -> Vec<NbtTag> {
+    let mut components = vec![];
+    for tag in extra {
+        let renamed = RENAMED_ENTRIES.iter().find(|(legacy, _)| *legacy == tag.name());
+        match (tag, renamed) {
+            (NbtTag::Int(_, value), Some((_, component))) => components.push(NbtTag::Int(component.to_string(), value)),
+            (NbtTag::Byte(name, unbreakable), _) if name == "Unbreakable" => if unbreakable != 0 {
+                components.push(NbtTag::Compound("minecraft:unbreakable".to_string(), NbtCompound::new()));
+            },
+            (NbtTag::Compound(name, mut display), _) if name == "display" => {
+                if let Some(NbtTag::List(_, lore @ (NbtList::End | NbtList::String(_)))) = display.get("Lore") {
+                    components.push(NbtTag::List("minecraft:lore".to_string(), lore.clone()));
+                    display.remove("Lore");
+                }
+                if let Some(NbtTag::Int(_, rgb)) = display.get("color") {
+                    let color = vec![NbtTag::Int("rgb".to_string(), *rgb)];
+                    components.push(NbtTag::Compound("minecraft:dyed_color".to_string(), color.into()));
+                    display.remove("color");
+                }
+                if !display.is_empty() {
+                    untranslated.push(NbtTag::Compound(name, display));
+                }
+            }
+            (tag, _) => untranslated.push(tag),
+        }
+    }
+    components
+}
+
+/// Translates the remaining components into entries of `tag`, moving the ones without an
+/// equivalent to `untranslated`.
+fn legacy_from_components(extra: Vec<NbtTag>, untranslated: &mut Vec<NbtTag>) -> Vec<NbtTag> {
+    let mut data = vec![];
+    let mut display = vec![];
+    for tag in extra {
+        let renamed = RENAMED_ENTRIES.iter().find(|(_, component)| *component == tag.name());
+        match (tag, renamed) {
+            (NbtTag::Int(_, value), Some((legacy, _))) => data.push(NbtTag::Int(legacy.to_string(), value)),
+            (NbtTag::Compound(name, fields), _) if name == "minecraft:unbreakable" && fields.is_empty() => {
+                data.push(NbtTag::Byte("Unbreakable".to_string(), 1));
+            }
+            (NbtTag::List(name, lore @ (NbtList::End | NbtList::String(_))), _) if name == "minecraft:lore" => {
+                display.push(NbtTag::List("Lore".to_string(), lore));
+            }
+            // Since 1.21.5 the color is stored without the `rgb` compound
+            (NbtTag::Int(name, rgb), _) if name == "minecraft:dyed_color" => {
+                display.push(NbtTag::Int("color".to_string(), rgb));
+            }
+            (NbtTag::Compound(name, fields), _) if name == "minecraft:dyed_color" && fields.len() == 1 => match fields.get("rgb") {
+                Some(NbtTag::Int(_, rgb)) => display.push(NbtTag::Int("color".to_string(), *rgb)),
+                _ => untranslated.push(NbtTag::Compound(name, fields)),
+            },
+            (tag, _) => untranslated.push(tag),
+        }
+    }
+    if !display.is_empty() {
+        data.push(NbtTag::Compound("display".to_string(), display.into()));
+    }
+    data
+}
+
+/// An item stack in an inventory, like the player's `Inventory` or `EnderItems`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryItem {
+    pub slot: i8,
+    pub item: ItemStack,
+}
+
+impl InventoryItem {
+    pub fn new(slot: i8, item: ItemStack) -> InventoryItem {
+        InventoryItem { slot, item }
+    }
+
+    pub fn from_tag(tag: &NbtTag) -> Result<InventoryItem, NbtParseError> {
+        InventoryItem::from_compound(compound(tag)?)
+    }
+
+    pub fn from_compound(tags: &NbtCompound) -> Result<InventoryItem, NbtParseError> {
+        let mut item = ItemStack::from_compound(tags)?;
+        item.other.retain(|tag| tag.name() != "Slot");
+        Ok(InventoryItem { slot: field(tags, "Slot", "byte", NbtTag::as_i8)?, item })
+    }
+
+    pub fn to_tag(&self) -> NbtTag {
+        self.item.to_tag_with("", self.item.format, vec![NbtTag::Byte("Slot".to_string(), self.slot)])
+    }
+
+    /// Reads a list of inventory items, like `Inventory`.
+    pub fn from_list(list: &NbtList) -> Result<Vec<InventoryItem>, NbtParseError> {
+        list.iter()
+            .map(|value| match value {
+                NbtValue::Compound(tags) => InventoryItem::from_compound(tags),
+                value => Err(WrongType("compound".to_string(), value.type_name())),
+            })
+            .collect()
+    }
+
+    pub fn to_list<S: Into<String>>(name: S, items: &[InventoryItem]) -> NbtTag {
+        // Like the game, an empty list is written without a type
+        let items = items.iter().map(|item| item.to_tag()).collect::<Vec<_>>();
+        NbtTag::List(name.into(), items.try_into().expect("items are compounds"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snbt_parser::parse_snbt;
+
+    #[test]
+    fn item_round_trip() {
+        let modern = NbtTag::Compound("".to_string(), vec![
+            NbtTag::Int("count".to_string(), 3),
+            NbtTag::Byte("Slot".to_string(), 2),
+            NbtTag::String("id".to_string(), "minecraft:stone".to_string()),
+        ].into());
+        let item = InventoryItem::from_tag(&modern).unwrap();
+        assert_eq!(item.slot, 2);
+        assert_eq!(item.item.count, 3);
+        assert!(item.item.other.is_empty());
+        assert_eq!(item.to_tag(), modern);
+
+        let legacy = NbtTag::Compound("".to_string(), vec![
+            NbtTag::String("id".to_string(), "minecraft:stone".to_string()),
+            NbtTag::Byte("Count".to_string(), 64),
+            NbtTag::Compound("tag".to_string(), vec![].into()),
+        ].into());
+        let item = ItemStack::from_tag(&legacy).unwrap();
+        assert_eq!(item.count, 64);
+        assert_eq!(item.format, ItemFormat::Legacy);
+        assert!(item.extra.is_empty());
+        assert_eq!(item.to_tag(""), legacy);
+    }
+
+    #[test]
+    fn convert_layouts() {
+        let legacy = NbtTag::Compound("".to_string(), vec![
+            NbtTag::String("id".to_string(), "minecraft:diamond_sword".to_string()),
+            NbtTag::Byte("Count".to_string(), 1),
+            NbtTag::Compound("tag".to_string(), vec![
+                NbtTag::Int("Damage".to_string(), 12),
+                NbtTag::Compound("display".to_string(), vec![
+                    NbtTag::String("Name".to_string(), "{\"text\":\"Sword\"}".to_string()),
+                    NbtTag::List("Lore".to_string(), NbtList::new(8)),
+                ].into()),
+                NbtTag::List("Enchantments".to_string(), vec![
+                    NbtTag::Compound("".to_string(), vec![
+                        NbtTag::String("id".to_string(), "minecraft:sharpness".to_string()),
+                        NbtTag::Short("lvl".to_string(), 5),
+                    ].into()),
+                ].try_into().unwrap()),
+                NbtTag::Byte("Unbreakable".to_string(), 1),
+            ].into()),
+        ].into());
+        let item = ItemStack::from_tag(&legacy).unwrap();
+        assert_eq!(item.damage, Some(12));
+        assert_eq!(item.custom_name.as_deref(), Some("{\"text\":\"Sword\"}"));
+        assert_eq!(item.enchantments, vec![Enchantment { id: "minecraft:sharpness".to_string(), level: 5 }]);
+        assert_eq!(item.extra.len(), 2);
+        assert_eq!(item.to_tag(""), legacy);
+
+        let modern = ItemStack::from_tag(&item.to_tag_as("", ItemFormat::Components)).unwrap();
+        assert_eq!(modern.format, ItemFormat::Components);
+        assert_eq!(modern.count, 1);
+        assert_eq!(modern.damage, item.damage);
+        assert_eq!(modern.custom_name, item.custom_name);
+        assert_eq!(modern.enchantments, item.enchantments);
+        let components = modern.to_tag("").get("components").unwrap();
+        assert_eq!(components.get("minecraft:enchantments").unwrap().get("levels").unwrap().get_int("minecraft:sharpness").unwrap(), 5);
+        assert_eq!(components.get_int("minecraft:damage").unwrap(), 12);
+        assert_eq!(components.get_compound("minecraft:unbreakable").unwrap(), NbtCompound::new());
+        assert_eq!(components.get_list("minecraft:lore").unwrap(), NbtList::new(8));
+        assert!(components.get_ref("Unbreakable").is_err() && components.get_ref("display").is_err());
+
+        let back = modern.to_tag_as("", ItemFormat::Legacy);
+        let data = back.get_ref("tag").unwrap();
+        assert_eq!(data.get_byte("Unbreakable").unwrap(), 1);
+        assert_eq!(data.get_ref("display").unwrap().get_list("Lore").unwrap(), NbtList::new(8));
+        let back = ItemStack::from_tag(&back).unwrap();
+        assert_eq!(back.enchantments, item.enchantments);
+        assert_eq!(back.custom_name, item.custom_name);
+    }
+
+    #[test]
+    fn report_untranslated() {
+        let legacy = parse_snbt(r#"{id:"minecraft:leather_helmet",Count:1b,tag:{HideFlags:2,RepairCost:3,display:{color:255,Name:'"Hat"',Unknown:1b}}}"#).unwrap();
+        let mut item = ItemStack::from_tag(&legacy).unwrap();
+        let untranslated = item.convert(ItemFormat::Components);
+        assert_eq!(untranslated, vec![
+            NbtTag::Int("HideFlags".to_string(), 2),
+            NbtTag::Compound("display".to_string(), vec![NbtTag::Byte("Unknown".to_string(), 1)].into()),
+        ]);
+        assert_eq!(item.to_tag(""), parse_snbt(r#"{id:"minecraft:leather_helmet",count:1,components:{"minecraft:repair_cost":3,"minecraft:dyed_color":{rgb:255},"minecraft:custom_name":'"Hat"'}}"#).unwrap());
+
+        let modern = parse_snbt(r#"{id:"minecraft:leather_helmet",count:1,components:{"minecraft:dyed_color":255,"minecraft:food":{nutrition:1}}}"#).unwrap();
+        let mut item = ItemStack::from_tag(&modern).unwrap();
+        assert_eq!(item.convert(ItemFormat::Legacy), vec![NbtTag::Compound("minecraft:food".to_string(), vec![NbtTag::Int("nutrition".to_string(), 1)].into())]);
+        assert_eq!(item.to_tag(""), parse_snbt(r#"{id:"minecraft:leather_helmet",Count:1b,tag:{display:{color:255}}}"#).unwrap());
+    }
+
+    #[test]
+    fn clamp_legacy_fields() {
+        let mut item = ItemStack::new("minecraft:stone", 200);
+        item.damage = Some(40000);
+        item.enchantments.push(Enchantment { id: "minecraft:sharpness".to_string(), level: 255 });
+        item.enchantments.push(Enchantment { id: "minecraft:smite".to_string(), level: 40000 });
+        assert_eq!(item.to_tag_as("", ItemFormat::PreFlattening), parse_snbt(r#"{id:"minecraft:stone",Count:127b,Damage:32767s,tag:{ench:[{id:16s,lvl:255s},{id:17s,lvl:32767s}]}}"#).unwrap());
+        item.count = -200;
+        assert_eq!(item.to_tag_as("", ItemFormat::Legacy).get_byte("Count").unwrap(), -128);
+    }
+
+    #[test]
+    fn enchantment_layouts() {
+        let old = parse_snbt(r#"{id:"minecraft:diamond_sword",Count:1b,Damage:12s,tag:{ench:[{id:16s,lvl:5s}]}}"#).unwrap();
+        let item = ItemStack::from_tag(&old).unwrap();
+        assert_eq!(item.format, ItemFormat::PreFlattening);
+        assert_eq!(item.damage, Some(12));
+        assert_eq!(item.enchantments, vec![Enchantment { id: "minecraft:sharpness".to_string(), level: 5 }]);
+        assert!(item.other.is_empty() && item.extra.is_empty());
+        assert_eq!(item.to_tag(""), old);
+        let legacy = item.to_tag_as("", ItemFormat::Legacy);
+        assert_eq!(legacy, parse_snbt(r#"{id:"minecraft:diamond_sword",Count:1b,tag:{Damage:12,Enchantments:[{id:"minecraft:sharpness",lvl:5s}]}}"#).unwrap());
+
+        let modded = parse_snbt(r#"{id:"mod:sword",Count:1b,Damage:0s,tag:{ench:[{id:200s,lvl:1s}]}}"#).unwrap();
+        let item = ItemStack::from_tag(&modded).unwrap();
+        assert!(item.enchantments.is_empty());
+        assert_eq!(item.to_tag(""), modded);
+
+        let flat = parse_snbt(r#"{id:"minecraft:bow",count:1,components:{"minecraft:enchantments":{"minecraft:power":2}}}"#).unwrap();
+        let item = ItemStack::from_tag(&flat).unwrap();
+        assert_eq!(item.enchantments, vec![Enchantment { id: "minecraft:power".to_string(), level: 2 }]);
+        assert_eq!(item.to_tag(""), flat);
+    }
+}
@@ -0,0 +1,240 @@
+use serde_json::{Map, Number, Value};
+use crate::NbtParseError;
+use crate::NbtParseError::{InvalidValue, WrongType};
+use crate::compound::NbtCompound;
+use crate::list::{type_name, NbtList};
+use crate::types::NbtTag;
+use crate::value::NbtValue;
+
+/// Conversion between NBT and JSON, enabled with the `json` feature.
+///
+/// The lossless form wraps every value in an object with its type, like
+/// `{"type":"short","value":5}`. Compounds map names to such objects, lists store the
+/// element type as `element_type`, and the root also stores its name. It converts back to
+/// an identical tag. The lossy form uses plain JSON values and is meant for display.
+pub mod nbt_json {
+    use super::*;
+
+    /// Converts a tree to lossless JSON.
+    pub fn to_json(tag: &NbtTag) -> Value {
+        let mut value = typed(tag);
+        if let Value::Object(object) = &mut value {
+            object.insert("name".to_string(), Value::String(tag.get_name()));
+        }
+        value
+    }
+
+    /// Reads lossless JSON written by [`to_json`].
+    pub fn from_json(value: &Value) -> Result<NbtTag, NbtParseError> {
+        let name = match value.get("name") {
+            Some(Value::String(name)) => name.clone(),
+            Some(value) => return Err(WrongType("string".to_string(), json_type(value))),
+            None => String::new(),
+        };
+        from_typed(name, value)
+    }
+
+    /// Converts a tree to plain JSON. Numbers lose their type, arrays become JSON arrays and
+    /// the root name is dropped. Non-finite floats become `null`.
+    pub fn to_json_lossy(tag: &NbtTag) -> Value {
+        tag.as_value().map_or(Value::Null, plain)
+    }
+
+    /// Converts plain JSON to a tree with an empty name. Integers become ints, or longs if
+    /// they don't fit, other numbers become doubles, booleans become bytes and arrays become
+    /// lists, which fails if their elements have different types.
+    pub fn from_json_lossy(value: &Value) -> Result<NbtTag, NbtParseError> {
+        lossy(String::new(), value)
+    }
+}
+
+fn plain(value: NbtValue<'_>) -> Value {
+    match value {
+        NbtValue::Byte(value) => Value::from(value),
+        NbtValue::Short(value) => Value::from(value),
+        NbtValue::Int(value) => Value::from(value),
+        NbtValue::Long(value) => Value::from(value),
+        NbtValue::Float(value) => Value::from(value),
+        NbtValue::Double(value) => Value::from(value),
+        NbtValue::ByteArray(values) => Value::from(values),
+        NbtValue::String(value) => Value::from(value),
+        NbtValue::List(values) => Value::Array(values.iter().map(plain).collect()),
+        NbtValue::Compound(tags) => Value::Object(tags.iter()
+            .map(|tag| (tag.get_name(), nbt_json::to_json_lossy(tag)))
+            .collect()),
+        NbtValue::IntArray(values) => Value::from(values),
+        NbtValue::LongArray(values) => Value::from(values),
+    }
+}
+
+fn typed(tag: &NbtTag) -> Value {
+    match tag.as_value() {
+        Some(value) => typed_value(value),
+        None => {
+            let mut object = Map::new();
+            object.insert("type".to_string(), Value::String(tag.type_name()));
+            object.insert("value".to_string(), Value::Null);
+            Value::Object(object)
+        }
+    }
+}
+
+fn typed_value(value: NbtValue<'_>) -> Value {
+    let payload = match value {
+        NbtValue::Byte(value) => Value::from(value),
+        NbtValue::Short(value) => Value::from(value),
+        NbtValue::Int(value) => Value::from(value),
+        NbtValue::Long(value) => Value::from(value),
+        NbtValue::Float(value) => float(value as f64),
+        NbtValue::Double(value) => float(value),
+        NbtValue::ByteArray(values) => Value::from(values),
+        NbtValue::String(value) => Value::from(value),
+        NbtValue::List(values) => Value::Array(values.iter().map(typed_value).collect()),
+        NbtValue::Compound(tags) => Value::Object(tags.iter().map(|tag| (tag.get_name(), typed(tag))).collect()),
+        NbtValue::IntArray(values) => Value::from(values),
+        NbtValue::LongArray(values) => Value::from(values),
+    };
+    let mut object = Map::new();
+    object.insert("type".to_string(), Value::String(value.type_name()));
+    if let NbtValue::List(values) = value {
+        object.insert("element_type".to_string(), Value::String(type_name(values.element_type())));
+    }
+    object.insert("value".to_string(), payload);
+    Value::Object(object)
+}
+
+/// JSON has no NaN or infinity, so those are written as strings.
+fn float(value: f64) -> Value {
+    match Number::from_f64(value) {
+        Some(number) => Value::Number(number),
+        None if value.is_nan() => Value::String("NaN".to_string()),
+        None if value > 0.0 => Value::String("Infinity".to_string()),
+        None => Value::String("-Infinity".to_string()),
+    }
+}
+
+fn from_typed(name: String, value: &Value) -> Result<NbtTag, NbtParseError> {
+    let field = |key: &str| value.get(key).ok_or_else(|| InvalidValue(format!("missing {key} in typed value")));
+    let type_name = field("type")?.as_str().ok_or_else(|| InvalidValue("type must be a string".to_string()))?;
+    let payload = field("value")?;
+    let wrong_type = || WrongType(type_name.to_string(), json_type(payload));
+    Ok(match type_name {
+        "byte" => NbtTag::Byte(name, integer(payload)?.try_into().map_err(|_| wrong_type())?),
+        "short" => NbtTag::Short(name, integer(payload)?.try_into().map_err(|_| wrong_type())?),
+        "int" => NbtTag::Int(name, integer(payload)?.try_into().map_err(|_| wrong_type())?),
+        "long" => NbtTag::Long(name, integer(payload)?),
+        "float" => NbtTag::Float(name, to_float(payload)? as f32),
+        "double" => NbtTag::Double(name, to_float(payload)?),
+        "byte array" => NbtTag::ByteArray(name, integers(payload)?),
+        "string" => NbtTag::String(name, payload.as_str().ok_or_else(wrong_type)?.to_string()),
+        "list" => {
+            let element_type = field("element_type")?.as_str().ok_or_else(wrong_type)?;
+            let element_type = (0..=12).find(|id| self::type_name(*id) == element_type)
+                .ok_or_else(|| InvalidValue(format!("unknown element type {element_type}")))?;
+            let mut list = NbtList::new(element_type);
+            for element in payload.as_array().ok_or_else(wrong_type)? {
+                list.push(from_typed(String::new(), element)?)?;
+            }
+            NbtTag::List(name, list)
+        }
+        "compound" => {
+            let mut compound = NbtCompound::new();
+            for (name, value) in payload.as_object().ok_or_else(wrong_type)? {
+                compound.push_unique(from_typed(name.clone(), value)?)?;
+            }
+            NbtTag::Compound(name, compound)
+        }
+        "int array" => NbtTag::IntArray(name, integers(payload)?),
+        "long array" => NbtTag::LongArray(name, integers(payload)?),
+        _ => return Err(InvalidValue(format!("unknown type {type_name}"))),
+    })
+}
+
+fn lossy(name: String, value: &Value) -> Result<NbtTag, NbtParseError> {
+    Ok(match value {
+        Value::Null => return Err(InvalidValue("null has no NBT representation".to_string())),
+        Value::Bool(value) => NbtTag::Byte(name, *value as i8),
+        Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+            (Some(n), _) => match i32::try_from(n) {
+                Ok(n) => NbtTag::Int(name, n),
+                Err(_) => NbtTag::Long(name, n),
+            },
+            (None, Some(n)) => NbtTag::Double(name, n),
+            _ => return Err(InvalidValue(format!("number {number} is out of range"))),
+        },
+        Value::String(value) => NbtTag::String(name, value.clone()),
+        Value::Array(values) => {
+            let tags = values.iter().map(|value| lossy(String::new(), value)).collect::<Result<Vec<_>, _>>()?;
+            NbtTag::List(name, tags.try_into()?)
+        }
+        Value::Object(object) => NbtTag::Compound(name, object.iter()
+            .map(|(name, value)| lossy(name.clone(), value))
+            .collect::<Result<_, _>>()?),
+    })
+}
+
+fn integer(value: &Value) -> Result<i64, NbtParseError> {
+    value.as_i64().ok_or_else(|| WrongType("integer".to_string(), json_type(value)))
+}
+
+fn integers<T: TryFrom<i64>>(value: &Value) -> Result<Vec<T>, NbtParseError> {
+    let values = value.as_array().ok_or_else(|| WrongType("array".to_string(), json_type(value)))?;
+    values.iter()
+        .map(|value| integer(value)?.try_into().map_err(|_| InvalidValue(format!("{value} is out of range"))))
+        .collect()
+}
+
+fn to_float(value: &Value) -> Result<f64, NbtParseError> {
+    match value {
+        Value::String(s) if s == "NaN" => Ok(f64::NAN),
+        Value::String(s) if s == "Infinity" => Ok(f64::INFINITY),
+        Value::String(s) if s == "-Infinity" => Ok(f64::NEG_INFINITY),
+        value => value.as_f64().ok_or_else(|| WrongType("number".to_string(), json_type(value))),
+    }
+}
+
+fn json_type(value: &Value) -> String {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::nbt_json::*;
+    use serde_json::json;
+    use crate::snbt_parser::parse_snbt;
+
+    #[test]
+    fn lossless_round_trip() {
+        let mut tag = parse_snbt(r#"{b:1b,s:5s,i:-3,l:9007199254740993L,f:0.1f,d:1.5d,inf:0d,ba:[B;1b],str:"x",
+            list:[[1s],[]],empty:[],c:{z:1,a:2},ia:[I;1,2],la:[L;-1L]}"#).unwrap();
+        tag.set_name("root");
+        tag.try_get_mut("inf").unwrap().set(NbtTag::Double("inf".to_string(), f64::INFINITY)).unwrap();
+        let json = to_json(&tag);
+        assert_eq!(json["name"], json!("root"));
+        assert_eq!(json["value"]["s"], json!({"type": "short", "value": 5}));
+        assert_eq!(json["value"]["list"]["value"][1], json!({"type": "list", "element_type": "end", "value": []}));
+        assert_eq!(json["value"]["inf"]["value"], json!("Infinity"));
+        assert_eq!(from_json(&json).unwrap(), tag);
+        assert_eq!(from_json(&serde_json::from_str(&json.to_string()).unwrap()).unwrap(), tag);
+        assert!(from_json(&json!({"type": "byte", "value": 300})).is_err());
+        assert!(from_json(&json!({"type": "list", "element_type": "int", "value": [{"type": "short", "value": 1}]})).is_err());
+    }
+
+    #[test]
+    fn lossy_conversion() {
+        let tag = parse_snbt(r#"{b:1b,Pos:[1.0d,2.5d],ia:[I;1,2],c:{name:"x"}}"#).unwrap();
+        let json = to_json_lossy(&tag);
+        assert_eq!(json, json!({"b": 1, "Pos": [1.0, 2.5], "ia": [1, 2], "c": {"name": "x"}}));
+        assert_eq!(from_json_lossy(&json!({"a": true, "big": 5000000000i64, "l": [1, 2]})).unwrap(),
+                   parse_snbt("{a:1b,big:5000000000L,l:[1,2]}").unwrap());
+        assert!(from_json_lossy(&json!([1, "a"])).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use std::sync::OnceLock;
+use crate::NbtParseError;
+use crate::NbtParseError::WrongLength;
+use crate::compound::NbtCompound;
+use crate::model::{compound, field, optional};
+use crate::nibble::NibbleArray;
+use crate::types::NbtTag;
+use crate::value::NbtValue;
+
+/// Number of blocks in a 16x16x16 section.
+pub const SECTION_VOLUME: usize = 4096;
+
+/// A section of a pre-1.13 chunk, storing numeric block ids and metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegacySection {
+    y: i8,
+    blocks: Vec<u8>,
+    add: Option<NibbleArray>,
+    data: NibbleArray,
+}
+
+impl LegacySection {
+    /// Reads a section compound containing `Y`, `Blocks`, `Data` and optionally `Add`.
+    pub fn from_tag(tag: &NbtTag) -> Result<LegacySection, NbtParseError> {
+        LegacySection::from_compound(compound(tag)?)
+    }
+
+    /// Reads a section from the children of its compound, like an element of `Sections`.
+    pub fn from_compound(tags: &NbtCompound) -> Result<LegacySection, NbtParseError> {
+        let blocks = field(tags, "Blocks", "byte array", NbtTag::as_byte_array)?;
+        if blocks.len() != SECTION_VOLUME {
+            return Err(WrongLength(SECTION_VOLUME, blocks.len()));
+        }
+        let add = optional(field(tags, "Add", "byte array", NbtTag::as_byte_array))?
+            .map(NibbleArray::from_bytes)
+            .transpose()?;
+        Ok(LegacySection {
+            y: field(tags, "Y", "byte", NbtTag::as_i8)?,
+            blocks: blocks.iter().map(|&n| n as u8).collect(),
+            add,
+            data: NibbleArray::from_bytes(field(tags, "Data", "byte array", NbtTag::as_byte_array)?)?,
+        })
+    }
+
+    /// The index of this section, where section 0 holds the blocks from Y 0 to 15.
+    pub fn y(&self) -> i8 {
+        self.y
+    }
+
+    /// Numeric block id at the given section local coordinates, including the `Add` bits.
+    pub fn block_id(&self, x: usize, y: usize, z: usize) -> u16 {
+        let index = Self::index(x, y, z);
+        let add = self.add.as_ref().map_or(0, |add| add.get_index(index));
+        self.blocks[index] as u16 | (add as u16) << 8
+    }
+
+    /// Block metadata at the given section local coordinates.
+    pub fn block_data(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.data.get_index(Self::index(x, y, z))
+    }
+
+    /// Flattened block name at the given section local coordinates, if the id is known.
+    pub fn block_name(&self, x: usize, y: usize, z: usize) -> Option<&'static str> {
+        legacy_block_name(self.block_id(x, y, z), self.block_data(x, y, z))
+    }
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        assert!(x < 16 && y < 16 && z < 16, "Position ({x}, {y}, {z}) is outside of the section");
+        y * 256 + z * 16 + x
+    }
+}
+
+/// A pre-1.13 chunk, with its sections stored inside the `Level` compound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegacyChunk {
+    pub x_pos: i32,
+    pub z_pos: i32,
+    pub sections: Vec<LegacySection>,
+}
+
+impl LegacyChunk {
+    /// Reads a legacy chunk from either the root tag of the chunk or its `Level` compound.
+    pub fn from_tag(tag: &NbtTag) -> Result<LegacyChunk, NbtParseError> {
+        let level = match tag.get_ref("Level") {
+            Ok(level) => level,
+            Err(NbtParseError::NoSuchValue(_)) => tag,
+            Err(e) => return Err(e),
+        };
+        let sections = field(compound(level)?, "Sections", "list", NbtTag::as_list)?
+            .iter()
+            .map(|section| match section {
+                NbtValue::Compound(tags) => LegacySection::from_compound(tags),
+                section => Err(NbtParseError::WrongType("compound".to_string(), section.type_name())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(LegacyChunk {
+            x_pos: level.get_int("xPos")?,
+            z_pos: level.get_int("zPos")?,
+            sections,
+        })
+    }
+
+    pub fn section(&self, y: i8) -> Option<&LegacySection> {
+        self.sections.iter().find(|section| section.y == y)
+    }
+
+    /// Numeric block id at the given chunk local coordinates. Missing sections are air.
+    pub fn block_id(&self, x: usize, y: i32, z: usize) -> u16 {
+        self.section_at(y).map_or(0, |section| section.block_id(x, y.rem_euclid(16) as usize, z))
+    }
+
+    /// Block metadata at the given chunk local coordinates.
+    pub fn block_data(&self, x: usize, y: i32, z: usize) -> u8 {
+        self.section_at(y).map_or(0, |section| section.block_data(x, y.rem_euclid(16) as usize, z))
+    }
+
+    /// Flattened block name at the given chunk local coordinates, if the id is known.
+    pub fn block_name(&self, x: usize, y: i32, z: usize) -> Option<&'static str> {
+        legacy_block_name(self.block_id(x, y, z), self.block_data(x, y, z))
+    }
+
+    fn section_at(&self, y: i32) -> Option<&LegacySection> {
+        i8::try_from(y.div_euclid(16)).ok().and_then(|section| self.section(section))
+    }
+}
+
+/// Converts a numeric block id and metadata into the modern flattened block name, e.g.
+/// `35:14` into `minecraft:red_wool`. Returns None for unknown ids and metadata.
+pub fn legacy_block_name(id: u16, data: u8) -> Option<&'static str> {
+    if id > 255 || data > 15 {
+        return None;
+    }
+    legacy_table()[(id as usize) << 4 | data as usize].as_deref()
+}
+
+const COLORS: [&str; 16] = [
+    "white", "orange", "magenta", "light_blue", "yellow", "lime", "pink", "gray",
+    "light_gray", "cyan", "purple", "blue", "brown", "green", "red", "black",
+];
+
+const WOODS: [&str; 6] = ["oak", "spruce", "birch", "jungle", "acacia", "dark_oak"];
+
+fn legacy_table() -> &'static [Option<String>] {
+    static TABLE: OnceLock<Vec<Option<String>>> = OnceLock::new();
+    TABLE.get_or_init(build_legacy_table)
+}
+
+fn build_legacy_table() -> Vec<Option<String>> {
+    let mut table = vec![None; 256 * 16];
+    // Sets the name for every data value where `data & mask` indexes into `names`
+    let mut variants = |id: usize, mask: u8, names: &[String]| {
+        for data in 0..16u8 {
+            if let Some(name) = names.get((data & mask) as usize) {
+                table[id << 4 | data as usize] = Some(format!("minecraft:{name}"));
+            }
+        }
+    };
+    let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let colored = |suffix: &str| COLORS.iter().map(|c| format!("{c}_{suffix}")).collect::<Vec<_>>();
+    let wooden = |suffix: &str| WOODS.iter().map(|w| format!("{w}_{suffix}")).collect::<Vec<_>>();
+
+    let simple: &[(usize, &str)] = &[
+        (0, "air"), (2, "grass_block"), (4, "cobblestone"), (7, "bedrock"), (8, "water"),
+        (9, "water"), (10, "lava"), (11, "lava"), (13, "gravel"), (14, "gold_ore"), (15, "iron_ore"),
+        (16, "coal_ore"), (20, "glass"), (21, "lapis_ore"), (22, "lapis_block"), (23, "dispenser"),
+        (25, "note_block"), (26, "red_bed"), (27, "powered_rail"), (28, "detector_rail"),
+        (29, "sticky_piston"), (30, "cobweb"), (32, "dead_bush"), (33, "piston"), (34, "piston_head"),
+        (36, "moving_piston"), (37, "dandelion"), (39, "brown_mushroom"), (40, "red_mushroom"),
+        (41, "gold_block"), (42, "iron_block"), (45, "bricks"), (46, "tnt"), (47, "bookshelf"),
+        (48, "mossy_cobblestone"), (49, "obsidian"), (51, "fire"), (52, "spawner"),
+        (53, "oak_stairs"), (54, "chest"), (55, "redstone_wire"), (56, "diamond_ore"),
+        (57, "diamond_block"), (58, "crafting_table"), (59, "wheat"), (60, "farmland"),
+        (61, "furnace"), (62, "furnace"), (63, "oak_sign"), (64, "oak_door"), (65, "ladder"),
+        (66, "rail"), (67, "cobblestone_stairs"), (68, "oak_wall_sign"), (69, "lever"),
+        (70, "stone_pressure_plate"), (71, "iron_door"), (72, "oak_pressure_plate"),
+        (73, "redstone_ore"), (74, "redstone_ore"), (77, "stone_button"), (78, "snow"), (79, "ice"),
+        (80, "snow_block"), (81, "cactus"), (82, "clay"), (83, "sugar_cane"), (84, "jukebox"),
+        (85, "oak_fence"), (86, "carved_pumpkin"), (87, "netherrack"), (88, "soul_sand"),
+        (89, "glowstone"), (90, "nether_portal"), (91, "jack_o_lantern"), (92, "cake"),
+        (93, "repeater"), (94, "repeater"), (96, "oak_trapdoor"), (101, "iron_bars"),
+        (102, "glass_pane"), (103, "melon"), (104, "pumpkin_stem"), (105, "melon_stem"),
+        (106, "vine"), (107, "oak_fence_gate"), (108, "brick_stairs"), (109, "stone_brick_stairs"),
+        (110, "mycelium"), (111, "lily_pad"), (112, "nether_bricks"), (113, "nether_brick_fence"),
+        (114, "nether_brick_stairs"), (115, "nether_wart"), (116, "enchanting_table"),
+        (117, "brewing_stand"), (119, "end_portal"), (120, "end_portal_frame"), (121, "end_stone"),
+        (122, "dragon_egg"), (123, "redstone_lamp"), (124, "redstone_lamp"), (127, "cocoa"),
+        (128, "sandstone_stairs"), (129, "emerald_ore"), (130, "ender_chest"),
+        (131, "tripwire_hook"), (132, "tripwire"), (133, "emerald_block"), (134, "spruce_stairs"),
+        (135, "birch_stairs"), (136, "jungle_stairs"), (137, "command_block"), (138, "beacon"),
+        (140, "flower_pot"), (141, "carrots"), (142, "potatoes"), (143, "oak_button"),
+        (146, "trapped_chest"), (147, "light_weighted_pressure_plate"),
+        (148, "heavy_weighted_pressure_plate"), (149, "comparator"), (150, "comparator"),
+        (151, "daylight_detector"), (152, "redstone_block"), (153, "nether_quartz_ore"),
+        (154, "hopper"), (156, "quartz_stairs"), (157, "activator_rail"), (158, "dropper"),
+        (163, "acacia_stairs"), (164, "dark_oak_stairs"), (165, "slime_block"), (166, "barrier"),
+        (167, "iron_trapdoor"), (169, "sea_lantern"), (170, "hay_block"), (172, "terracotta"),
+        (173, "coal_block"), (174, "packed_ice"), (176, "white_banner"), (177, "white_wall_banner"),
+        (178, "daylight_detector"), (180, "red_sandstone_stairs"), (181, "red_sandstone_slab"),
+        (182, "red_sandstone_slab"), (183, "spruce_fence_gate"), (184, "birch_fence_gate"),
+        (185, "jungle_fence_gate"), (186, "dark_oak_fence_gate"), (187, "acacia_fence_gate"),
+        (188, "spruce_fence"), (189, "birch_fence"), (190, "jungle_fence"), (191, "dark_oak_fence"),
+        (192, "acacia_fence"), (193, "spruce_door"), (194, "birch_door"), (195, "jungle_door"),
+        (196, "acacia_door"), (197, "dark_oak_door"), (198, "end_rod"), (199, "chorus_plant"),
+        (200, "chorus_flower"), (201, "purpur_block"), (202, "purpur_pillar"),
+        (203, "purpur_stairs"), (204, "purpur_slab"), (205, "purpur_slab"),
+        (206, "end_stone_bricks"), (207, "beetroots"), (208, "dirt_path"), (209, "end_gateway"),
+        (210, "repeating_command_block"), (211, "chain_command_block"), (212, "frosted_ice"),
+        (213, "magma_block"), (214, "nether_wart_block"), (215, "red_nether_bricks"),
+        (216, "bone_block"), (217, "structure_void"), (218, "observer"), (255, "structure_block"),
+    ];
+    for (id, name) in simple {
+        variants(*id, 0, &names(&[name]));
+    }
+
+    variants(1, 0xf, &names(&[
+        "stone", "granite", "polished_granite", "diorite", "polished_diorite", "andesite",
+        "polished_andesite",
+    ]));
+    variants(3, 0xf, &names(&["dirt", "coarse_dirt", "podzol"]));
+    variants(5, 0xf, &wooden("planks"));
+    variants(6, 0x7, &wooden("sapling"));
+    variants(12, 0xf, &names(&["sand", "red_sand"]));
+    variants(17, 0x3, &wooden("log")[..4]);
+    variants(18, 0x3, &wooden("leaves")[..4]);
+    variants(19, 0xf, &names(&["sponge", "wet_sponge"]));
+    variants(24, 0xf, &names(&["sandstone", "chiseled_sandstone", "cut_sandstone"]));
+    variants(31, 0xf, &names(&["dead_bush", "short_grass", "fern"]));
+    variants(35, 0xf, &colored("wool"));
+    variants(38, 0xf, &names(&[
+        "poppy", "blue_orchid", "allium", "azure_bluet", "red_tulip", "orange_tulip",
+        "white_tulip", "pink_tulip", "oxeye_daisy",
+    ]));
+    let stone_slabs = names(&[
+        "smooth_stone_slab", "sandstone_slab", "petrified_oak_slab", "cobblestone_slab",
+        "brick_slab", "stone_brick_slab", "nether_brick_slab", "quartz_slab",
+    ]);
+    variants(43, 0x7, &stone_slabs);
+    variants(44, 0x7, &stone_slabs);
+    variants(50, 0xf, &names(&["torch", "wall_torch", "wall_torch", "wall_torch", "wall_torch", "torch"]));
+    let redstone_torches = names(&[
+        "redstone_torch", "redstone_wall_torch", "redstone_wall_torch", "redstone_wall_torch",
+        "redstone_wall_torch", "redstone_torch",
+    ]);
+    variants(75, 0xf, &redstone_torches);
+    variants(76, 0xf, &redstone_torches);
+    variants(95, 0xf, &colored("stained_glass"));
+    variants(97, 0xf, &names(&[
+        "infested_stone", "infested_cobblestone", "infested_stone_bricks",
+        "infested_mossy_stone_bricks", "infested_cracked_stone_bricks",
+        "infested_chiseled_stone_bricks",
+    ]));
+    variants(98, 0xf, &names(&[
+        "stone_bricks", "mossy_stone_bricks", "cracked_stone_bricks", "chiseled_stone_bricks",
+    ]));
+    let mut brown_mushroom = vec!["brown_mushroom_block".to_string(); 16];
+    let mut red_mushroom = vec!["red_mushroom_block".to_string(); 16];
+    for data in [10, 15] {
+        brown_mushroom[data] = "mushroom_stem".to_string();
+        red_mushroom[data] = "mushroom_stem".to_string();
+    }
+    variants(99, 0xf, &brown_mushroom);
+    variants(100, 0xf, &red_mushroom);
+    variants(118, 0xf, &names(&["cauldron", "water_cauldron", "water_cauldron", "water_cauldron"]));
+    variants(125, 0x7, &wooden("slab"));
+    variants(126, 0x7, &wooden("slab"));
+    variants(139, 0xf, &names(&["cobblestone_wall", "mossy_cobblestone_wall"]));
+    variants(144, 0xf, &names(&[
+        "skeleton_skull", "skeleton_skull", "skeleton_wall_skull", "skeleton_wall_skull",
+        "skeleton_wall_skull", "skeleton_wall_skull",
+    ]));
+    let anvils = names(&["anvil", "chipped_anvil", "damaged_anvil"]);
+    variants(145, 0xf, &(0..12).map(|data| anvils[data >> 2].clone()).collect::<Vec<_>>());
+    variants(155, 0xf, &names(&[
+        "quartz_block", "chiseled_quartz_block", "quartz_pillar", "quartz_pillar", "quartz_pillar",
+    ]));
+    variants(159, 0xf, &colored("terracotta"));
+    variants(160, 0xf, &colored("stained_glass_pane"));
+    variants(161, 0x3, &wooden("leaves")[4..]);
+    variants(162, 0x3, &wooden("log")[4..]);
+    variants(168, 0xf, &names(&["prismarine", "prismarine_bricks", "dark_prismarine"]));
+    variants(171, 0xf, &colored("carpet"));
+    variants(175, 0xf, &names(&["sunflower", "lilac", "tall_grass", "large_fern", "rose_bush", "peony"]));
+    variants(179, 0xf, &names(&["red_sandstone", "chiseled_red_sandstone", "cut_red_sandstone"]));
+    for (i, color) in COLORS.iter().enumerate() {
+        variants(219 + i, 0, &[format!("{color}_shulker_box")]);
+        variants(235 + i, 0, &[format!("{color}_glazed_terracotta")]);
+    }
+    variants(251, 0xf, &colored("concrete"));
+    variants(252, 0xf, &colored("concrete_powder"));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_names() {
+        assert_eq!(legacy_block_name(0, 0), Some("minecraft:air"));
+        assert_eq!(legacy_block_name(1, 3), Some("minecraft:diorite"));
+        assert_eq!(legacy_block_name(1, 9), None);
+        assert_eq!(legacy_block_name(17, 6), Some("minecraft:birch_log"));
+        assert_eq!(legacy_block_name(35, 14), Some("minecraft:red_wool"));
+        assert_eq!(legacy_block_name(145, 9), Some("minecraft:damaged_anvil"));
+        assert_eq!(legacy_block_name(162, 1), Some("minecraft:dark_oak_log"));
+        assert_eq!(legacy_block_name(243, 0), Some("minecraft:light_gray_glazed_terracotta"));
+        assert_eq!(legacy_block_name(300, 0), None);
+    }
+
+    #[test]
+    fn read_chunk() {
+        let mut blocks = vec![0i8; SECTION_VOLUME];
+        let mut data = NibbleArray::new();
+        let mut add = NibbleArray::new();
+        // x = 1, y = 2, z = 3
+        blocks[2 * 256 + 3 * 16 + 1] = 35;
+        data.set(1, 2, 3, 4);
+        blocks[0] = 1;
+        add.set(0, 0, 0, 1);
+        let chunk = NbtTag::Compound("".to_string(), vec![
+            NbtTag::Compound("Level".to_string(), vec![
+                NbtTag::Int("xPos".to_string(), 2),
+                NbtTag::Int("zPos".to_string(), -1),
+                NbtTag::List("Sections".to_string(), vec![
+                    NbtTag::Compound("".to_string(), vec![
+                        NbtTag::Byte("Y".to_string(), 4),
+                        NbtTag::ByteArray("Blocks".to_string(), blocks),
+                        data.to_tag("Data"),
+                        add.to_tag("Add"),
+                    ].into()),
+                ].try_into().unwrap()),
+            ].into()),
+        ].into());
+        let chunk = LegacyChunk::from_tag(&chunk).unwrap();
+        assert_eq!((chunk.x_pos, chunk.z_pos), (2, -1));
+        assert_eq!(chunk.block_id(1, 66, 3), 35);
+        assert_eq!(chunk.block_data(1, 66, 3), 4);
+        assert_eq!(chunk.block_name(1, 66, 3), Some("minecraft:yellow_wool"));
+        assert_eq!(chunk.block_id(0, 64, 0), 257);
+        assert_eq!(chunk.block_name(0, 64, 0), None);
+        assert_eq!(chunk.block_name(0, 10, 0), Some("minecraft:air"));
+    }
+}
@@ -0,0 +1,349 @@
+use crate::NbtParseError;
+use crate::NbtParseError::InvalidValue;
+use crate::model::{compound, bool_tag, get_bool, get_string_list, key_order, optional, ordered_compound, string_list, unknown_fields};
+use crate::compound::NbtCompound;
+use crate::types::NbtTag;
+
+/// The game mode of a world or player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameType {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameType {
+    pub fn id(&self) -> i32 {
+        match self {
+            GameType::Survival => 0,
+            GameType::Creative => 1,
+            GameType::Adventure => 2,
+            GameType::Spectator => 3,
+        }
+    }
+}
+
+impl TryFrom<i32> for GameType {
+    type Error = NbtParseError;
+
+    fn try_from(id: i32) -> Result<Self, Self::Error> {
+        match id {
+            0 => Ok(GameType::Survival),
+            1 => Ok(GameType::Creative),
+            2 => Ok(GameType::Adventure),
+            3 => Ok(GameType::Spectator),
+            id => Err(InvalidValue(format!("unknown game type {id}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn id(&self) -> i8 {
+        match self {
+            Difficulty::Peaceful => 0,
+            Difficulty::Easy => 1,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 3,
+        }
+    }
+}
+
+impl TryFrom<i8> for Difficulty {
+    type Error = NbtParseError;
+
+    fn try_from(id: i8) -> Result<Self, Self::Error> {
+        match id {
+            0 => Ok(Difficulty::Peaceful),
+            1 => Ok(Difficulty::Easy),
+            2 => Ok(Difficulty::Normal),
+            3 => Ok(Difficulty::Hard),
+            id => Err(InvalidValue(format!("unknown difficulty {id}"))),
+        }
+    }
+}
+
+/// The `GameRules` compound. Every rule is stored as a string, in the original order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GameRules {
+    rules: Vec<(String, String)>,
+}
+
+impl GameRules {
+    pub fn from_tag(tag: &NbtTag) -> Result<GameRules, NbtParseError> {
+        let NbtTag::Compound(_, tags) = tag else {
+            return Err(NbtParseError::WrongType("compound".to_string(), tag.type_name()));
+        };
+        let rules = tags.iter().map(|tag| match tag {
+            NbtTag::String(name, value) => Ok((name.clone(), value.clone())),
+            tag => Err(NbtParseError::WrongType("string".to_string(), tag.type_name())),
+        }).collect::<Result<_, _>>()?;
+        Ok(GameRules { rules })
+    }
+
+    pub fn to_tag(&self) -> NbtTag {
+        NbtTag::Compound("GameRules".to_string(), self.rules.iter()
+            .map(|(name, value)| NbtTag::String(name.clone(), value.clone()))
+            .collect())
+    }
+
+    pub fn get(&self, rule: &str) -> Option<&str> {
+        self.rules.iter().find(|(name, _)| name == rule).map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_bool(&self, rule: &str) -> Option<bool> {
+        self.get(rule).and_then(|value| value.parse().ok())
+    }
+
+    pub fn get_int(&self, rule: &str) -> Option<i32> {
+        self.get(rule).and_then(|value| value.parse().ok())
+    }
+
+    /// Sets a rule, replacing its old value if it already exists.
+    pub fn set<S: Into<String>, V: ToString>(&mut self, rule: S, value: V) {
+        let rule = rule.into();
+        let value = value.to_string();
+        match self.rules.iter_mut().find(|(name, _)| *name == rule) {
+            Some((_, old)) => *old = value,
+            None => self.rules.push((rule, value)),
+        }
+    }
+
+    pub fn remove(&mut self, rule: &str) -> Option<String> {
+        let index = self.rules.iter().position(|(name, _)| name == rule)?;
+        Some(self.rules.remove(index).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.rules.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+/// The `WorldGenSettings` compound. Dimensions are kept as raw NBT, since their layout
+/// depends heavily on the generator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldGenSettings {
+    pub seed: i64,
+    pub generate_features: bool,
+    pub bonus_chest: bool,
+    pub dimensions: NbtTag,
+    pub other: Vec<NbtTag>,
+    key_order: Vec<String>,
+}
+
+impl WorldGenSettings {
+    const KNOWN: [&'static str; 4] = ["seed", "generate_features", "bonus_chest", "dimensions"];
+
+    /// Settings generating features and no bonus chest, with the given `dimensions` children.
+    pub fn new(seed: i64, dimensions: NbtCompound) -> WorldGenSettings {
+        WorldGenSettings {
+            seed,
+            generate_features: true,
+            bonus_chest: false,
+            dimensions: NbtTag::Compound("dimensions".to_string(), dimensions),
+            other: vec![],
+            key_order: vec![],
+        }
+    }
+
+    pub fn from_tag(tag: &NbtTag) -> Result<WorldGenSettings, NbtParseError> {
+        Ok(WorldGenSettings {
+            seed: tag.get_long("seed")?,
+            generate_features: get_bool(tag, "generate_features")?,
+            bonus_chest: get_bool(tag, "bonus_chest")?,
+            dimensions: tag.get("dimensions")?,
+            other: unknown_fields(compound(tag)?, &Self::KNOWN),
+            key_order: key_order(compound(tag)?),
+        })
+    }
+
+    pub fn to_tag(&self) -> NbtTag {
+        let mut tags = vec![
+            NbtTag::Long("seed".to_string(), self.seed),
+            bool_tag("generate_features", self.generate_features),
+            bool_tag("bonus_chest", self.bonus_chest),
+            self.dimensions.clone(),
+        ];
+        tags.extend(self.other.iter().cloned());
+        ordered_compound("WorldGenSettings", &self.key_order, tags)
+    }
+}
+
+/// The `DataPacks` compound, listing enabled and disabled data packs.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DataPacks {
+    pub enabled: Vec<String>,
+    pub disabled: Vec<String>,
+    pub other: Vec<NbtTag>,
+    key_order: Vec<String>,
+}
+
+impl DataPacks {
+    const KNOWN: [&'static str; 2] = ["Enabled", "Disabled"];
+
+    pub fn new(enabled: Vec<String>, disabled: Vec<String>) -> DataPacks {
+        DataPacks { enabled, disabled, ..DataPacks::default() }
+    }
+
+    pub fn from_tag(tag: &NbtTag) -> Result<DataPacks, NbtParseError> {
+        Ok(DataPacks {
+            enabled: get_string_list(tag, "Enabled")?,
+            disabled: get_string_list(tag, "Disabled")?,
+            other: unknown_fields(compound(tag)?, &Self::KNOWN),
+            key_order: key_order(compound(tag)?),
+        })
+    }
+
+    pub fn to_tag(&self) -> NbtTag {
+        let mut tags = vec![
+            string_list("Disabled", &self.disabled),
+            string_list("Enabled", &self.enabled),
+        ];
+        tags.extend(self.other.iter().cloned());
+        ordered_compound("DataPacks", &self.key_order, tags)
+    }
+}
+
+/// Typed model of the `Data` compound in level.dat. Keys without a typed field are kept in
+/// `other`, and the original key order is restored when converting back, so a read and write
+/// cycle loses nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelData {
+    pub level_name: String,
+    pub data_version: Option<i32>,
+    pub spawn_x: i32,
+    pub spawn_y: i32,
+    pub spawn_z: i32,
+    pub spawn_angle: Option<f32>,
+    /// Total ticks the world has been running.
+    pub time: i64,
+    /// Time of day in ticks, not reset each day.
+    pub day_time: i64,
+    pub game_type: GameType,
+    pub difficulty: Option<Difficulty>,
+    pub difficulty_locked: Option<bool>,
+    pub hardcore: bool,
+    pub allow_commands: Option<bool>,
+    pub world_gen_settings: Option<WorldGenSettings>,
+    pub game_rules: Option<GameRules>,
+    pub data_packs: Option<DataPacks>,
+    pub other: Vec<NbtTag>,
+    key_order: Vec<String>,
+}
+
+impl LevelData {
+    const KNOWN: [&'static str; 16] = [
+        "LevelName", "DataVersion", "SpawnX", "SpawnY", "SpawnZ", "SpawnAngle", "Time", "DayTime",
+        "GameType", "Difficulty", "DifficultyLocked", "hardcore", "allowCommands",
+        "WorldGenSettings", "GameRules", "DataPacks",
+    ];
+
+    /// A survival world spawning at the origin, with every optional field left out.
+    pub fn new<S: Into<String>>(level_name: S) -> LevelData {
+        LevelData {
+            level_name: level_name.into(),
+            data_version: None,
+            spawn_x: 0,
+            spawn_y: 0,
+            spawn_z: 0,
+            spawn_angle: None,
+            time: 0,
+            day_time: 0,
+            game_type: GameType::Survival,
+            difficulty: None,
+            difficulty_locked: None,
+            hardcore: false,
+            allow_commands: None,
+            world_gen_settings: None,
+            game_rules: None,
+            data_packs: None,
+            other: vec![],
+            key_order: vec![],
+        }
+    }
+
+    /// Reads the model from either the root tag of level.dat or its `Data` compound.
+    pub fn from_tag(tag: &NbtTag) -> Result<LevelData, NbtParseError> {
+        let data = match tag.get_ref("Data") {
+            Ok(data) => data,
+            Err(NbtParseError::NoSuchValue(_)) => tag,
+            Err(e) => return Err(e),
+        };
+        Ok(LevelData {
+            level_name: data.get_string("LevelName")?,
+            data_version: optional(data.get_int("DataVersion"))?,
+            spawn_x: data.get_int("SpawnX")?,
+            spawn_y: data.get_int("SpawnY")?,
+            spawn_z: data.get_int("SpawnZ")?,
+            spawn_angle: optional(data.get_float("SpawnAngle"))?,
+            time: data.get_long("Time")?,
+            day_time: data.get_long("DayTime")?,
+            game_type: data.get_int("GameType")?.try_into()?,
+            difficulty: optional(data.get_byte("Difficulty"))?.map(Difficulty::try_from).transpose()?,
+            difficulty_locked: optional(get_bool(data, "DifficultyLocked"))?,
+            hardcore: get_bool(data, "hardcore")?,
+            allow_commands: optional(get_bool(data, "allowCommands"))?,
+            world_gen_settings: optional(data.get_ref("WorldGenSettings"))?
+                .map(WorldGenSettings::from_tag).transpose()?,
+            game_rules: optional(data.get_ref("GameRules"))?
+                .map(GameRules::from_tag).transpose()?,
+            data_packs: optional(data.get_ref("DataPacks"))?
+                .map(DataPacks::from_tag).transpose()?,
+            other: unknown_fields(compound(data)?, &Self::KNOWN),
+            key_order: key_order(compound(data)?),
+        })
+    }
+
+    /// Converts the model back into the `Data` compound.
+    pub fn to_tag(&self) -> NbtTag {
+        let mut tags = vec![
+            NbtTag::String("LevelName".to_string(), self.level_name.clone()),
+            NbtTag::Int("SpawnX".to_string(), self.spawn_x),
+            NbtTag::Int("SpawnY".to_string(), self.spawn_y),
+            NbtTag::Int("SpawnZ".to_string(), self.spawn_z),
+            NbtTag::Long("Time".to_string(), self.time),
+            NbtTag::Long("DayTime".to_string(), self.day_time),
+            NbtTag::Int("GameType".to_string(), self.game_type.id()),
+            bool_tag("hardcore", self.hardcore),
+        ];
+        if let Some(difficulty) = self.difficulty {
+            tags.push(NbtTag::Byte("Difficulty".to_string(), difficulty.id()));
+        }
+        if let Some(difficulty_locked) = self.difficulty_locked {
+            tags.push(bool_tag("DifficultyLocked", difficulty_locked));
+        }
+        if let Some(allow_commands) = self.allow_commands {
+            tags.push(bool_tag("allowCommands", allow_commands));
+        }
+        if let Some(data_version) = self.data_version {
+            tags.push(NbtTag::Int("DataVersion".to_string(), data_version));
+        }
+        if let Some(spawn_angle) = self.spawn_angle {
+            tags.push(NbtTag::Float("SpawnAngle".to_string(), spawn_angle));
+        }
+        if let Some(world_gen_settings) = &self.world_gen_settings {
+            tags.push(world_gen_settings.to_tag());
+        }
+        if let Some(game_rules) = &self.game_rules {
+            tags.push(game_rules.to_tag());
+        }
+        if let Some(data_packs) = &self.data_packs {
+            tags.push(data_packs.to_tag());
+        }
+        tags.extend(self.other.iter().cloned());
+        ordered_compound("Data", &self.key_order, tags)
+    }
+
+    /// Converts the model into a root tag, as stored in level.dat.
+    pub fn to_root(&self) -> NbtTag {
+        NbtTag::Compound("".to_string(), vec![self.to_tag()].into())
+    }
+}
@@ -1,14 +1,65 @@
 mod types;
+mod compound;
+mod list;
+mod value;
 mod parser;
+mod visitor;
+mod select;
+mod borrowed;
 mod parse_error;
+mod heightmap;
+mod nibble;
+mod legacy;
+mod block_state;
+mod writer;
+mod endian;
+mod snbt;
+mod path;
+mod diff;
+mod merge;
+mod tree;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "async")]
+mod async_io;
+mod model;
+mod level;
+mod item;
+mod player;
+mod uuid;
 
 pub use types::*;
+pub use compound::*;
+pub use list::*;
+pub use value::*;
 pub use parser::*;
+pub use visitor::*;
+pub use select::*;
+pub use borrowed::*;
 pub use parse_error::*;
+pub use heightmap::*;
+pub use nibble::*;
+pub use legacy::*;
+pub use block_state::*;
+pub use writer::*;
+pub use endian::*;
+pub use snbt::*;
+pub use path::*;
+pub use diff::*;
+pub use tree::*;
+#[cfg(feature = "json")]
+pub use json::*;
+#[cfg(feature = "async")]
+pub use async_io::*;
+pub use level::*;
+pub use item::*;
+pub use player::*;
+pub use uuid::*;
 
 #[cfg(test)]
 mod tests {
     use std::fs;
+    use std::io::Read;
     use std::path::PathBuf;
     use super::*;
 
@@ -29,8 +80,98 @@ mod tests {
         parse_file("level.dat");
     }
 
+    #[test]
+    fn level_dat_model() {
+        let tree = parse_file("level.dat");
+        let level = LevelData::from_tag(&tree).unwrap();
+        assert_eq!(level.level_name, "New World");
+        assert_eq!(level.data_version, Some(3837));
+        assert_eq!((level.spawn_x, level.spawn_y, level.spawn_z), (-32, 67, -48));
+        assert_eq!(level.time, 1590);
+        assert_eq!(level.game_type, GameType::Creative);
+        assert_eq!(level.difficulty, Some(Difficulty::Peaceful));
+        assert_eq!(level.game_rules.as_ref().unwrap().get_bool("keepInventory"), Some(false));
+        assert_eq!(level.game_rules.as_ref().unwrap().get_int("randomTickSpeed"), Some(3));
+        assert_eq!(level.world_gen_settings.as_ref().unwrap().seed, -3084804315914809234);
+        assert_eq!(level.data_packs.as_ref().unwrap().enabled[0], "vanilla");
+        assert!(level.other.iter().any(|tag| tag.get_name() == "Player"));
+        assert_eq!(level.to_root(), tree);
+
+        let mut edited = level.clone();
+        edited.game_rules.as_mut().unwrap().set("keepInventory", true);
+        edited.difficulty = Some(Difficulty::Hard);
+        let edited = LevelData::from_tag(&nbt_parser::parse_binary(nbt_writer::write_binary(&edited.to_root()).unwrap()).unwrap()).unwrap();
+        assert_eq!(edited.game_rules.unwrap().get_bool("keepInventory"), Some(true));
+        assert_eq!(edited.difficulty, Some(Difficulty::Hard));
+
+        let mut data = tree.get("Data").unwrap();
+        for name in ["Difficulty", "DifficultyLocked", "allowCommands", "GameRules"] {
+            data.remove(name).unwrap();
+        }
+        let mut data_packs = data.get("DataPacks").unwrap();
+        data_packs.insert(NbtTag::List("Pending".to_string(), vec!["file/a".to_string()].into())).unwrap();
+        data.insert(data_packs).unwrap();
+        let level = LevelData::from_tag(&data).unwrap();
+        assert_eq!((level.difficulty, level.difficulty_locked, level.allow_commands), (None, None, None));
+        assert_eq!(level.game_rules, None);
+        assert_eq!(level.to_tag(), data);
+    }
+
+    #[test]
+    fn build_level_dat() {
+        let mut level = LevelData::new("Built");
+        level.world_gen_settings = Some(WorldGenSettings::new(42, NbtCompound::new()));
+        level.data_packs = Some(DataPacks::new(vec!["vanilla".to_string()], vec![]));
+        let data = level.to_tag();
+        assert!(data.get_ref("GameRules").is_err());
+        assert_eq!(data.get_ref("WorldGenSettings").unwrap().get_long("seed").unwrap(), 42);
+        assert_eq!(LevelData::from_tag(&data).unwrap().to_tag(), data);
+    }
+
+    #[test]
+    fn write_level_dat() {
+        let mut test_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        test_file.push("test_files/level.dat");
+        let mut data = vec![];
+        flate2::read::GzDecoder::new(fs::read(test_file).unwrap().as_slice()).read_to_end(&mut data).unwrap();
+        let tree = nbt_parser::parse_binary(data.clone()).unwrap();
+        assert_eq!(nbt_writer::write_binary(&tree).unwrap(), data);
+    }
+
     #[test]
     fn parse_player_dat() {
         parse_file("player.dat");
     }
+
+    #[test]
+    fn player_dat_model() {
+        let tree = parse_file("player.dat");
+        let player = PlayerData::from_tag(&tree).unwrap();
+        assert_eq!(player.pos, [-51.17448417173605, 64.0, -48.427735010662914]);
+        assert_eq!(player.rotation, [1.7999816, 16.799997]);
+        assert_eq!(player.health, 20.0);
+        assert_eq!(player.food_level, 20);
+        assert_eq!(player.game_type, GameType::Creative);
+        assert_eq!(player.dimension, "minecraft:overworld");
+        assert_eq!(player.inventory.len(), 2);
+        assert_eq!(player.inventory_slot(3).unwrap().item.id, "opencomputers:screen3");
+        assert_eq!(player.ender_items, Some(vec![]));
+        assert_eq!(player.to_tag(""), tree);
+
+        let mut legacy = tree.clone();
+        legacy.insert(NbtTag::Int("Dimension".to_string(), -1)).unwrap();
+        legacy.remove("EnderItems").unwrap();
+        legacy.remove("SelectedItemSlot").unwrap();
+        let mut player = PlayerData::from_tag(&legacy).unwrap();
+        assert_eq!((player.dimension.as_str(), player.selected_item_slot), ("minecraft:the_nether", None));
+        assert_eq!(player.to_tag(""), legacy);
+        player.dimension = "minecraft:the_end".to_string();
+        assert_eq!(player.to_tag("").get_int("Dimension").unwrap(), 1);
+        player.dimension = "custom:dimension".to_string();
+        assert_eq!(player.to_tag("").get_string("Dimension").unwrap(), "custom:dimension");
+
+        let level = parse_file("level.dat");
+        let embedded = PlayerData::from_tag(&level.get("Data").unwrap().get("Player").unwrap()).unwrap();
+        assert_eq!(embedded.inventory, player.inventory);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,372 @@
+use std::ops::Range;
+use crate::NbtParseError;
+use crate::NbtParseError::{IndexOutOfBounds, InvalidValue, WrongType};
+use crate::compound::NbtCompound;
+use crate::types::NbtTag;
+use crate::value::{NbtValue, NbtValueMut};
+
+/// The elements of a list tag, stored by type without names. The type is kept for empty
+/// lists too, so they are written back exactly as they were read. An empty list that was
+/// never given a type is `End` and takes the type of the first element pushed into it.
+///
+/// All elements have the same type: adding an element of another type fails, and mutable
+/// access to an element can only change its value.
+///
+/// Since elements aren't tags, lists have no `Index<usize>` returning one. [`NbtList::get`]
+/// and [`NbtTag::try_index`] return a borrowed [`NbtValue`] instead.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum NbtList {
+    /// An empty list without an element type
+    #[default]
+    End,
+    Byte(Vec<i8>),
+    Short(Vec<i16>),
+    Int(Vec<i32>),
+    Long(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    ByteArray(Vec<Vec<i8>>),
+    String(Vec<String>),
+    List(Vec<NbtList>),
+    Compound(Vec<NbtCompound>),
+    IntArray(Vec<Vec<i32>>),
+    LongArray(Vec<Vec<i64>>),
+}
+
+/// Runs `$body` with `$values` bound to the elements of a typed list, or gives `$end` for
+/// an untyped one.
+macro_rules! each_type {
+    ($list:expr, $values:ident => $body:expr, End => $end:expr) => {
+        match $list {
+            NbtList::End => $end,
+            NbtList::Byte($values) => $body,
+            NbtList::Short($values) => $body,
+            NbtList::Int($values) => $body,
+            NbtList::Long($values) => $body,
+            NbtList::Float($values) => $body,
+            NbtList::Double($values) => $body,
+            NbtList::ByteArray($values) => $body,
+            NbtList::String($values) => $body,
+            NbtList::List($values) => $body,
+            NbtList::Compound($values) => $body,
+            NbtList::IntArray($values) => $body,
+            NbtList::LongArray($values) => $body,
+        }
+    };
+}
+
+impl NbtList {
+    /// Creates an empty list holding elements with the given type id. Unknown ids give an
+    /// untyped list.
+    pub fn new(element_type: i8) -> NbtList {
+        NbtList::with_capacity(element_type, 0)
+    }
+
+    pub fn with_capacity(element_type: i8, capacity: usize) -> NbtList {
+        match element_type {
+            1 => NbtList::Byte(Vec::with_capacity(capacity)),
+            2 => NbtList::Short(Vec::with_capacity(capacity)),
+            3 => NbtList::Int(Vec::with_capacity(capacity)),
+            4 => NbtList::Long(Vec::with_capacity(capacity)),
+            5 => NbtList::Float(Vec::with_capacity(capacity)),
+            6 => NbtList::Double(Vec::with_capacity(capacity)),
+            7 => NbtList::ByteArray(Vec::with_capacity(capacity)),
+            8 => NbtList::String(Vec::with_capacity(capacity)),
+            9 => NbtList::List(Vec::with_capacity(capacity)),
+            10 => NbtList::Compound(Vec::with_capacity(capacity)),
+            11 => NbtList::IntArray(Vec::with_capacity(capacity)),
+            12 => NbtList::LongArray(Vec::with_capacity(capacity)),
+            _ => NbtList::End,
+        }
+    }
+
+    /// The type id of the elements, `0` for an untyped empty list.
+    pub fn element_type(&self) -> i8 {
+        match self {
+            NbtList::End => 0,
+            NbtList::Byte(_) => 1,
+            NbtList::Short(_) => 2,
+            NbtList::Int(_) => 3,
+            NbtList::Long(_) => 4,
+            NbtList::Float(_) => 5,
+            NbtList::Double(_) => 6,
+            NbtList::ByteArray(_) => 7,
+            NbtList::String(_) => 8,
+            NbtList::List(_) => 9,
+            NbtList::Compound(_) => 10,
+            NbtList::IntArray(_) => 11,
+            NbtList::LongArray(_) => 12,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        each_type!(self, values => values.len(), End => 0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value of the element at `index`.
+    pub fn get(&self, index: usize) -> Option<NbtValue<'_>> {
+        each_type!(self, values => values.get(index).map(Element::value), End => None)
+    }
+
+    /// Returns mutable access to the value of an element, which can't change its type.
+    pub fn get_mut(&mut self, index: usize) -> Option<NbtValueMut<'_>> {
+        each_type!(self, values => values.get_mut(index).map(Element::value_mut), End => None)
+    }
+
+    pub fn iter(&self) -> NbtListIter<'_> {
+        NbtListIter { list: self, indices: 0..self.len() }
+    }
+
+    /// Iterates mutably over the values of the elements, which can't change their type.
+    pub fn iter_mut(&mut self) -> Box<dyn Iterator<Item = NbtValueMut<'_>> + '_> {
+        each_type!(self, values => Box::new(values.iter_mut().map(Element::value_mut)), End => Box::new(std::iter::empty()))
+    }
+
+    pub fn push(&mut self, element: NbtTag) -> Result<(), NbtParseError> {
+        self.insert(self.len(), element)
+    }
+
+    /// Inserts an element at `index`, shifting the following elements. The name of the
+    /// element is dropped.
+    pub fn insert(&mut self, index: usize, element: NbtTag) -> Result<(), NbtParseError> {
+        if element.type_id() == 0 {
+            return Err(InvalidValue("end tags can't be inserted into a list".to_string()));
+        }
+        let len = self.len();
+        if index > len {
+            return Err(IndexOutOfBounds(index, len));
+        }
+        if *self == NbtList::End {
+            *self = NbtList::new(element.type_id());
+        }
+        let element_type = self.element_type();
+        each_type!(self, values => {
+            let value = Element::from_tag(element).map_err(|tag| WrongType(type_name(element_type), tag.type_name()))?;
+            values.insert(index, value);
+        }, End => unreachable!("the list was given a type"));
+        Ok(())
+    }
+
+    /// Replaces the element at `index` and returns the old element. The new element must
+    /// have the list's type.
+    pub fn set(&mut self, index: usize, element: NbtTag) -> Result<NbtTag, NbtParseError> {
+        let element_type = self.element_type();
+        let len = self.len();
+        each_type!(self, values => {
+            let old = values.get_mut(index).ok_or(IndexOutOfBounds(index, len))?;
+            let value = Element::from_tag(element).map_err(|tag| WrongType(type_name(element_type), tag.type_name()))?;
+            Ok(std::mem::replace(old, value).into_tag())
+        }, End => Err(IndexOutOfBounds(index, 0)))
+    }
+
+    /// Removes and returns the element at `index`. The list keeps its type when it becomes empty.
+    pub fn remove(&mut self, index: usize) -> Option<NbtTag> {
+        each_type!(self, values => (index < values.len()).then(|| values.remove(index).into_tag()), End => None)
+    }
+
+    /// Keeps only the elements for which `f` returns true. The list keeps its type.
+    pub fn retain<F: FnMut(NbtValue<'_>) -> bool>(&mut self, mut f: F) {
+        each_type!(self, values => values.retain(|value| f(value.value())), End => {})
+    }
+
+    /// Converts the elements to tags with empty names.
+    pub fn into_vec(self) -> Vec<NbtTag> {
+        each_type!(self, values => values.into_iter().map(Element::into_tag).collect(), End => vec![])
+    }
+}
+
+/// Name of a type id, as returned by `NbtTag::type_name`.
+pub(crate) fn type_name(id: i8) -> String {
+    match id {
+        0 => "end",
+        1 => "byte",
+        2 => "short",
+        3 => "int",
+        4 => "long",
+        5 => "float",
+        6 => "double",
+        7 => "byte array",
+        8 => "string",
+        9 => "list",
+        10 => "compound",
+        11 => "int array",
+        12 => "long array",
+        _ => "unknown",
+    }.to_string()
+}
+
+/// An iterator over the values of the elements of a list.
+#[derive(Debug, Clone)]
+pub struct NbtListIter<'a> {
+    list: &'a NbtList,
+    indices: Range<usize>,
+}
+
+impl<'a> Iterator for NbtListIter<'a> {
+    type Item = NbtValue<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indices.next().and_then(|index| self.list.get(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for NbtListIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.indices.next_back().and_then(|index| self.list.get(index))
+    }
+}
+
+impl ExactSizeIterator for NbtListIter<'_> {}
+
+impl TryFrom<Vec<NbtTag>> for NbtList {
+    type Error = NbtParseError;
+
+    /// Builds a list from elements of the same type. The type of an empty vector is End.
+    fn try_from(elements: Vec<NbtTag>) -> Result<Self, Self::Error> {
+        let mut list = NbtList::with_capacity(elements.first().map_or(0, NbtTag::type_id), elements.len());
+        for element in elements {
+            list.push(element)?;
+        }
+        Ok(list)
+    }
+}
+
+impl IntoIterator for NbtList {
+    type Item = NbtTag;
+    type IntoIter = std::vec::IntoIter<NbtTag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a NbtList {
+    type Item = NbtValue<'a>;
+    type IntoIter = NbtListIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A payload type stored in a list.
+trait Element: Sized {
+    fn value(&self) -> NbtValue<'_>;
+
+    fn value_mut(&mut self) -> NbtValueMut<'_>;
+
+    fn into_tag(self) -> NbtTag;
+
+    /// Takes the value out of a tag of this type, or gives the tag back.
+    fn from_tag(tag: NbtTag) -> Result<Self, NbtTag>;
+}
+
+macro_rules! typed_list {
+    ($type:ty, $variant:ident, $name:literal $(, $deref:tt)?) => {
+        impl Element for $type {
+            fn value(&self) -> NbtValue<'_> {
+                NbtValue::$variant($($deref)? self)
+            }
+
+            fn value_mut(&mut self) -> NbtValueMut<'_> {
+                NbtValueMut::$variant(self)
+            }
+
+            fn into_tag(self) -> NbtTag {
+                NbtTag::$variant(String::new(), self)
+            }
+
+            fn from_tag(tag: NbtTag) -> Result<Self, NbtTag> {
+                match tag {
+                    NbtTag::$variant(_, value) => Ok(value),
+                    tag => Err(tag),
+                }
+            }
+        }
+
+        impl From<Vec<$type>> for NbtList {
+            fn from(values: Vec<$type>) -> Self {
+                NbtList::$variant(values)
+            }
+        }
+
+        impl TryFrom<&NbtList> for Vec<$type> {
+            type Error = NbtParseError;
+
+            /// Copies the elements out of a list of this type. Empty lists of any type are accepted.
+            fn try_from(list: &NbtList) -> Result<Self, Self::Error> {
+                match list {
+                    NbtList::$variant(values) => Ok(values.clone()),
+                    list if list.is_empty() => Ok(vec![]),
+                    list => Err(WrongType($name.to_string(), type_name(list.element_type()))),
+                }
+            }
+        }
+    };
+}
+
+typed_list!(i8, Byte, "byte", *);
+typed_list!(i16, Short, "short", *);
+typed_list!(i32, Int, "int", *);
+typed_list!(i64, Long, "long", *);
+typed_list!(f32, Float, "float", *);
+typed_list!(f64, Double, "double", *);
+typed_list!(Vec<i8>, ByteArray, "byte array");
+typed_list!(String, String, "string");
+typed_list!(NbtList, List, "list");
+typed_list!(NbtCompound, Compound, "compound");
+typed_list!(Vec<i32>, IntArray, "int array");
+typed_list!(Vec<i64>, LongArray, "long array");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn homogeneous() {
+        let mut list = NbtList::new(3);
+        assert!(matches!(list.push(NbtTag::Short("".to_string(), 1)), Err(WrongType(_, _))));
+        list.push(NbtTag::Int("named".to_string(), 2)).unwrap();
+        assert_eq!(list, NbtList::Int(vec![2]));
+        assert_eq!(list.get(0), Some(NbtValue::Int(2)));
+        list.remove(0);
+        assert_eq!(list.element_type(), 3);
+
+        let mut untyped = NbtList::default();
+        assert!(untyped.push(NbtTag::End).is_err());
+        untyped.push(NbtTag::String("".to_string(), "a".to_string())).unwrap();
+        assert_eq!(untyped.element_type(), 8);
+        assert!(NbtList::try_from(vec![NbtTag::Byte("".to_string(), 1), NbtTag::Int("".to_string(), 1)]).is_err());
+    }
+
+    #[test]
+    fn edit_elements() {
+        let mut list = NbtList::from(vec!["a".to_string(), "b".to_string()]);
+        if let Some(NbtValueMut::String(value)) = list.get_mut(1) {
+            value.push('c');
+        }
+        assert!(matches!(list.set(0, NbtTag::Int("".to_string(), 1)), Err(WrongType(_, _))));
+        assert!(matches!(list.set(2, NbtTag::String("".to_string(), "d".to_string())), Err(IndexOutOfBounds(2, 2))));
+        assert_eq!(list.set(0, NbtTag::String("x".to_string(), "d".to_string())).unwrap(), NbtTag::String("".to_string(), "a".to_string()));
+        list.retain(|value| value.as_str() != Some("bc"));
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![NbtValue::String("d")]);
+        assert_eq!(list.into_vec(), vec![NbtTag::String("".to_string(), "d".to_string())]);
+    }
+
+    #[test]
+    fn typed_conversions() {
+        let list = NbtList::from(vec![1.5f64, -2.0]);
+        assert_eq!(list.element_type(), 6);
+        assert_eq!(Vec::<f64>::try_from(&list).unwrap(), vec![1.5, -2.0]);
+        assert!(matches!(Vec::<f32>::try_from(&list), Err(WrongType(_, _))));
+        assert_eq!(NbtList::from(Vec::<String>::new()).element_type(), 8);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::NbtParseError;
+use crate::NbtParseError::NoSuchValue;
+use crate::diff::{NbtChange, NbtDiff};
+use crate::compound::NbtCompound;
+use crate::types::NbtTag;
+use crate::value::NbtValueMut;
+
+impl NbtTag {
+    /// Merges another tree into this one, like `/data merge`: compounds are merged
+    /// recursively, and any other value of `other` replaces the value in this tree. This tag
+    /// keeps its own name.
+    pub fn merge(&mut self, other: &NbtTag) {
+        match (&mut *self, other) {
+            (NbtTag::Compound(_, tags), NbtTag::Compound(_, others)) => merge_compound(tags, others),
+            (tag, other) => {
+                let name = tag.get_name();
+                *tag = other.clone();
+                tag.set_name(name);
+            }
+        }
+    }
+
+    /// Replays a diff on this tree. Added values are inserted into lists and compounds,
+    /// removed values are removed and changed values are replaced. The old values stored in
+    /// the diff aren't checked, so a patch can be applied to trees that differ from the one
+    /// it was made from. Fails when a path of a change doesn't match anything, leaving this
+    /// tree unchanged.
+    pub fn apply_patch(&mut self, patch: &NbtDiff) -> Result<(), NbtParseError> {
+        let mut patched = self.clone();
+        patched.replay(patch)?;
+        *self = patched;
+        Ok(())
+    }
+
+    fn replay(&mut self, patch: &NbtDiff) -> Result<(), NbtParseError> {
+        for change in &patch.changes {
+            match change {
+                NbtChange::Added { path, value } => {
+                    path.insert(self, value.clone())?;
+                }
+                NbtChange::Removed { path, .. } => {
+                    if path.remove(self) == 0 {
+                        return Err(NoSuchValue(path.to_string()));
+                    }
+                }
+                NbtChange::Changed { path, new, .. } | NbtChange::TypeChanged { path, new, .. } => {
+                    if path.is_root() {
+                        let name = self.get_name();
+                        *self = new.clone();
+                        self.set_name(name);
+                    } else {
+                        path.set(self, new.clone())?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn merge_compound(tags: &mut NbtCompound, others: &NbtCompound) {
+    for other in others {
+        match (tags.get_mut(other.name()), other) {
+            (Some(NbtValueMut::Compound(tags)), NbtTag::Compound(_, others)) => merge_compound(tags, others),
+            _ => {
+                tags.insert(other.clone()).expect("children of a compound aren't End");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::DiffOptions;
+    use crate::snbt_parser::parse_snbt;
+
+    #[test]
+    fn merge_trees() {
+        let mut tag = parse_snbt(r#"{GameRules:{keepInventory:"false",doFireTick:"true"},Pos:[1.0d],Name:"a"}"#).unwrap();
+        tag.set_name("Data");
+        let preset = parse_snbt(r#"{GameRules:{keepInventory:"true"},Pos:[2.0d,3.0d],Extra:1b}"#).unwrap();
+        tag.merge(&preset);
+        let mut expected = parse_snbt(r#"{GameRules:{keepInventory:"true",doFireTick:"true"},Pos:[2.0d,3.0d],Name:"a",Extra:1b}"#).unwrap();
+        expected.set_name("Data");
+        assert_eq!(tag, expected);
+    }
+
+    #[test]
+    fn apply_diff() {
+        let old = parse_snbt(r#"{Time:1L,Gone:1b,Pos:[1.0d,2.0d,3.0d],Mode:1,Inventory:[{Slot:0b,id:"stone"},{Slot:1b,id:"dirt"}]}"#).unwrap();
+        let new = parse_snbt(r#"{Time:2L,Pos:[1.0d,2.5d,3.0d,4.0d],Mode:"creative",Inventory:[{Slot:1b,id:"sand"},{Slot:2b,id:"dirt"}],New:{a:[]}}"#).unwrap();
+        for options in [DiffOptions::new(), DiffOptions::new().with_list_key("Slot")] {
+            let diff = NbtDiff::with_options(&old, &new, &options);
+            let mut patched = old.clone();
+            patched.apply_patch(&NbtDiff::from_tag(&diff.to_tag("")).unwrap()).unwrap();
+            assert!(patched.diff(&new).is_empty(), "{}", patched.diff(&new));
+        }
+
+        let shrunk = parse_snbt(r#"{Pos:[1.0d]}"#).unwrap();
+        let mut patched = parse_snbt(r#"{Pos:[1.0d,2.0d,3.0d]}"#).unwrap();
+        patched.apply_patch(&patched.diff(&shrunk)).unwrap();
+        assert_eq!(patched, shrunk);
+        let mut unpatched = shrunk.clone();
+        assert!(matches!(unpatched.apply_patch(&old.diff(&new)), Err(NoSuchValue(_))));
+        assert_eq!(unpatched, shrunk);
+    }
+}
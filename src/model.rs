@@ -0,0 +1,75 @@
+use crate::NbtParseError;
+use crate::NbtParseError::{NoSuchValue, TriedGettingFromNonCompound, WrongType};
+use crate::compound::NbtCompound;
+use crate::list::NbtList;
+use crate::types::NbtTag;
+
+/// Turns a missing value into None, keeping every other error.
+pub(crate) fn optional<T>(result: Result<T, NbtParseError>) -> Result<Option<T>, NbtParseError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(NbtParseError::NoSuchValue(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Borrows the children of a compound tag.
+pub(crate) fn compound(tag: &NbtTag) -> Result<&NbtCompound, NbtParseError> {
+    tag.as_compound().ok_or_else(|| TriedGettingFromNonCompound(tag.type_name()))
+}
+
+/// Borrows the value of a child with one of the `NbtTag::as_*` accessors, failing like the
+/// typed getters when it's missing or has another type.
+pub(crate) fn field<'a, T>(
+    tags: &'a NbtCompound,
+    name: &str,
+    expected: &str,
+    value: fn(&'a NbtTag) -> Option<T>,
+) -> Result<T, NbtParseError> {
+    let tag = tags.get(name).ok_or_else(|| NoSuchValue(name.to_string()))?;
+    value(tag).ok_or_else(|| WrongType(expected.to_string(), tag.type_name()))
+}
+
+pub(crate) fn get_bool(tag: &NbtTag, name: &str) -> Result<bool, NbtParseError> {
+    Ok(tag.get_byte(name)? != 0)
+}
+
+pub(crate) fn bool_tag(name: &str, value: bool) -> NbtTag {
+    NbtTag::Byte(name.to_string(), value as i8)
+}
+
+/// The children of a compound whose names aren't in `known`.
+pub(crate) fn unknown_fields(tags: &NbtCompound, known: &[&str]) -> Vec<NbtTag> {
+    tags.iter()
+        .filter(|tag| !known.contains(&tag.name()))
+        .cloned()
+        .collect()
+}
+
+/// The names of the children of a compound, in their original order.
+pub(crate) fn key_order(tags: &NbtCompound) -> Vec<String> {
+    tags.iter().map(|tag| tag.get_name()).collect()
+}
+
+/// Builds a compound where the children named in `order` keep that order, followed by any
+/// children that weren't in the original compound.
+pub(crate) fn ordered_compound(name: &str, order: &[String], mut tags: Vec<NbtTag>) -> NbtTag {
+    let mut ordered = vec![];
+    for key in order {
+        if let Some(index) = tags.iter().position(|tag| tag.name() == key) {
+            ordered.push(tags.remove(index));
+        }
+    }
+    ordered.append(&mut tags);
+    NbtTag::Compound(name.to_string(), ordered.into())
+}
+
+pub(crate) fn string_list(name: &str, strings: &[String]) -> NbtTag {
+    // Like the game, an empty list is written without a type
+    let list = if strings.is_empty() { NbtList::default() } else { NbtList::from(strings.to_vec()) };
+    NbtTag::List(name.to_string(), list)
+}
+
+pub(crate) fn get_string_list(tag: &NbtTag, name: &str) -> Result<Vec<String>, NbtParseError> {
+    Vec::<String>::try_from(field(compound(tag)?, name, "list", NbtTag::as_list)?)
+}
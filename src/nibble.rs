@@ -0,0 +1,120 @@
+use crate::NbtParseError;
+use crate::NbtParseError::WrongLength;
+use crate::model::{compound, field};
+use crate::types::NbtTag;
+
+/// Number of bytes in a section sized nibble array.
+pub const NIBBLE_ARRAY_LEN: usize = 2048;
+
+/// An array of 4-bit values for a 16x16x16 section, used for `BlockLight`, `SkyLight` and the
+/// legacy `Data` and `Add` arrays. Values are indexed by `y * 256 + z * 16 + x`, with the even
+/// indices stored in the low nibble of each byte.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NibbleArray {
+    data: Vec<u8>,
+}
+
+impl NibbleArray {
+    /// Creates an array where every value is 0.
+    pub fn new() -> NibbleArray {
+        Self::filled(0)
+    }
+
+    /// Creates an array where every value is 15, i.e. full light.
+    pub fn full() -> NibbleArray {
+        Self::filled(15)
+    }
+
+    /// Creates an array where every value is `value`. Only the lowest 4 bits are used.
+    pub fn filled(value: u8) -> NibbleArray {
+        let value = value & 0xf;
+        NibbleArray { data: vec![value | value << 4; NIBBLE_ARRAY_LEN] }
+    }
+
+    pub fn from_bytes(bytes: &[i8]) -> Result<NibbleArray, NbtParseError> {
+        if bytes.len() != NIBBLE_ARRAY_LEN {
+            return Err(WrongLength(NIBBLE_ARRAY_LEN, bytes.len()));
+        }
+        Ok(NibbleArray { data: bytes.iter().map(|n| *n as u8).collect() })
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.get_index(Self::index(x, y, z))
+    }
+
+    /// Sets the value at the given section local coordinates. Only the lowest 4 bits are used.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: u8) {
+        self.set_index(Self::index(x, y, z), value)
+    }
+
+    pub fn get_index(&self, index: usize) -> u8 {
+        let byte = self.data[index / 2];
+        if index & 1 == 0 {
+            byte & 0xf
+        } else {
+            byte >> 4
+        }
+    }
+
+    pub fn set_index(&mut self, index: usize, value: u8) {
+        let byte = &mut self.data[index / 2];
+        if index & 1 == 0 {
+            *byte = (*byte & 0xf0) | (value & 0xf);
+        } else {
+            *byte = (*byte & 0x0f) | (value & 0xf) << 4;
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<i8> {
+        self.data.iter().map(|n| *n as i8).collect()
+    }
+
+    /// Converts the array into a byte array tag with the given name.
+    pub fn to_tag<S: Into<String>>(&self, name: S) -> NbtTag {
+        NbtTag::ByteArray(name.into(), self.to_bytes())
+    }
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        assert!(x < 16 && y < 16 && z < 16, "Position ({x}, {y}, {z}) is outside of the section");
+        y * 256 + z * 16 + x
+    }
+}
+
+impl Default for NibbleArray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NbtTag {
+    pub fn get_nibble_array<S: AsRef<str>>(&self, name: S) -> Result<NibbleArray, NbtParseError> {
+        NibbleArray::from_bytes(field(compound(self)?, name.as_ref(), "byte array", NbtTag::as_byte_array)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_set() {
+        let mut light = NibbleArray::new();
+        light.set(0, 0, 0, 15);
+        light.set(1, 0, 0, 7);
+        light.set(15, 15, 15, 0x1f);
+        assert_eq!(light.get(0, 0, 0), 15);
+        assert_eq!(light.get(1, 0, 0), 7);
+        assert_eq!(light.get(15, 15, 15), 15);
+        assert_eq!(light.to_bytes()[0], 0x7f);
+        assert_eq!(NibbleArray::full().get(4, 8, 12), 15);
+    }
+
+    #[test]
+    fn tag_round_trip() {
+        let mut light = NibbleArray::full();
+        light.set(2, 3, 4, 1);
+        let section = NbtTag::Compound("".to_string(), vec![light.to_tag("SkyLight")].into());
+        assert_eq!(section.get_nibble_array("SkyLight").unwrap(), light);
+        assert!(matches!(NibbleArray::from_bytes(&[0; 16]), Err(WrongLength(2048, 16))));
+    }
+}
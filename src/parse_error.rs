@@ -1,8 +1,11 @@
+use std::io;
 use std::string::FromUtf8Error;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum NbtParseError {
+    #[error("Failed reading data: {0}")]
+    Io(#[from] io::Error),
     #[error("Failed parsing UTF-8 string: {0}")]
     StringUtf8Error(#[from] FromUtf8Error),
     #[error("Hit end of data")]
@@ -15,4 +18,16 @@ pub enum NbtParseError {
     WrongType(String, String),
     #[error("Tried to get named child of non-compound tag. Actual type is {0}")]
     TriedGettingFromNonCompound(String),
-}
\ No newline at end of file
+    #[error("Compound contains the key {0} more than once")]
+    DuplicateKey(String),
+    #[error("Tried to index into non-list tag. Actual type is {0}")]
+    TriedIndexingNonList(String),
+    #[error("Index {0} is out of bounds for list of length {1}")]
+    IndexOutOfBounds(usize, usize),
+    #[error("Expected {0} elements, got {1}")]
+    WrongLength(usize, usize),
+    #[error("Invalid value: {0}")]
+    InvalidValue(String),
+    #[error("Invalid syntax at position {1}: {0}")]
+    Syntax(String, usize),
+}
@@ -1,283 +1,280 @@
-#![allow(dead_code)]
-use std::iter::Peekable;
-use std::slice::Iter;
 use crate::parse_error::NbtParseError;
 use crate::types::NbtTag;
+use crate::compound::NbtCompound;
+use crate::list::NbtList;
+use crate::borrowed::NbtTagRef;
+use crate::visitor::NbtArray;
+
+/// Compression of an NBT file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NbtCompression {
+    None,
+    Gzip,
+    Zlib,
+}
+
+impl NbtCompression {
+    /// Detects the compression from the header of the data. Anything that isn't gzip or
+    /// zlib is assumed to be uncompressed.
+    pub fn detect(data: &[u8]) -> NbtCompression {
+        match data {
+            [0x1f, 0x8b, ..] => NbtCompression::Gzip,
+            [cmf, flg, ..] if cmf & 0x0f == 8 && u16::from_be_bytes([*cmf, *flg]).is_multiple_of(31) => NbtCompression::Zlib,
+            _ => NbtCompression::None,
+        }
+    }
+}
 
 pub mod nbt_parser {
-    use std::io;
     use std::io::Read;
     use flate2::read::{GzDecoder, ZlibDecoder};
+    use crate::endian::nbt_endian;
     use super::*;
 
-    pub fn parse_gzip(compressed: Vec<u8>) -> io::Result<NbtTag> {
+    pub fn parse_gzip(compressed: Vec<u8>) -> Result<NbtTag, NbtParseError> {
         let mut decompresser = GzDecoder::new(compressed.as_slice());
         let mut data = vec![];
         let _ = decompresser.read_to_end(&mut data)?;
-        Ok(parse_binary(data))
+        parse_binary(data)
     }
 
-    pub fn parse_zlib(compressed: Vec<u8>) -> io::Result<NbtTag> {
+    pub fn parse_zlib(compressed: Vec<u8>) -> Result<NbtTag, NbtParseError> {
         let mut decompresser = ZlibDecoder::new(compressed.as_slice());
         let mut data = vec![];
         let _ = decompresser.read_to_end(&mut data)?;
-        Ok(parse_binary(data))
-    }
-
-    pub fn parse_binary(data_vec: Vec<u8>) -> NbtTag {
-        let mut data = data_vec.iter().peekable();
-        parse_next(&mut data).unwrap()
-    }
-
-    fn parse_next(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        match next_byte(iterable)? {
-            0 => parse_end(iterable),
-            1 => parse_byte(iterable),
-            2 => parse_short(iterable),
-            3 => parse_int(iterable),
-            4 => parse_long(iterable),
-            5 => parse_float(iterable),
-            6 => parse_double(iterable),
-            7 => parse_byte_arr(iterable),
-            8 => parse_string(iterable),
-            9 => parse_list(iterable),
-            10 => parse_compound(iterable),
-            11 => parse_int_array(iterable),
-            12 => parse_long_array(iterable),
-            id => Err(NbtParseError::UnknownNBT(id)),
-        }
-    }
-
-    fn parse_end(_: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        Ok(NbtTag::End)
+        parse_binary(data)
     }
 
-    fn next(iterable: &mut Peekable<Iter<u8>>) -> Result<u8, NbtParseError> {
-        iterable.next().map(|n| *n).ok_or(NbtParseError::EndOfData)
+    /// Decompresses data with the detected compression, see [`NbtCompression::detect`].
+    pub fn decompress(data: Vec<u8>) -> Result<(Vec<u8>, NbtCompression), NbtParseError> {
+        let compression = NbtCompression::detect(&data);
+        let mut decompressed = vec![];
+        match compression {
+            NbtCompression::None => return Ok((data, compression)),
+            NbtCompression::Gzip => GzDecoder::new(data.as_slice()).read_to_end(&mut decompressed)?,
+            NbtCompression::Zlib => ZlibDecoder::new(data.as_slice()).read_to_end(&mut decompressed)?,
+        };
+        Ok((decompressed, compression))
     }
 
-    fn next_byte(iterable: &mut Peekable<Iter<u8>>) -> Result<i8, NbtParseError> {
-        Ok(i8::from_be_bytes([next(iterable)?]))
+    /// Parses gzip, zlib or uncompressed binary NBT, detecting the compression.
+    pub fn parse_any(data: Vec<u8>) -> Result<NbtTag, NbtParseError> {
+        parse_binary(decompress(data)?.0)
     }
 
-    fn next_short(iterable: &mut Peekable<Iter<u8>>) -> Result<i16, NbtParseError> {
-        Ok(i16::from_be_bytes([next(iterable)?, next(iterable)?]))
+    /// Parses uncompressed little-endian binary NBT, as used by Bedrock Edition.
+    pub fn parse_binary_le(data: Vec<u8>) -> Result<NbtTag, NbtParseError> {
+        parse_binary(nbt_endian::little_to_big(&data)?)
     }
 
-    fn next_int(iterable: &mut Peekable<Iter<u8>>) -> Result<i32, NbtParseError> {
-        Ok(i32::from_be_bytes([
-            next(iterable)?, next(iterable)?, next(iterable)?, next(iterable)?
-        ]))
+    /// Parses uncompressed binary NBT into a tree borrowing from `data`.
+    pub fn parse_borrowed(data: &[u8]) -> Result<NbtTagRef<'_>, NbtParseError> {
+        Scanner { data, pos: 0 }.named_ref()
     }
 
-    fn next_long(iterable: &mut Peekable<Iter<u8>>) -> Result<i64, NbtParseError> {
-        Ok(i64::from_be_bytes([
-            next(iterable)?, next(iterable)?, next(iterable)?, next(iterable)?,
-            next(iterable)?, next(iterable)?, next(iterable)?, next(iterable)?
-        ]))
-    }
-
-    fn next_float(iterable: &mut Peekable<Iter<u8>>) -> Result<f32, NbtParseError> {
-        Ok(f32::from_be_bytes([
-            next(iterable)?, next(iterable)?, next(iterable)?, next(iterable)?
-        ]))
+    pub fn parse_binary(data_vec: Vec<u8>) -> Result<NbtTag, NbtParseError> {
+        Scanner { data: &data_vec, pos: 0 }.named()
     }
+}
 
-    fn next_double(iterable: &mut Peekable<Iter<u8>>) -> Result<f64, NbtParseError> {
-        Ok(f64::from_be_bytes([
-            next(iterable)?, next(iterable)?, next(iterable)?, next(iterable)?,
-            next(iterable)?, next(iterable)?, next(iterable)?, next(iterable)?
-        ]))
-    }
+/// A cursor over binary NBT, the decoder behind the parsers and the visitor API. Reads are
+/// bounds-checked once per value or array, and strings and arrays are borrowed from the data.
+pub(crate) struct Scanner<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) pos: usize,
+}
 
-    fn next_byte_arr(iterable: &mut Peekable<Iter<u8>>) -> Result<Vec<i8>, NbtParseError> {
-        let size = next_int(iterable)?;
-        let arr = iterable.take(size as usize).map(|n| *n as i8).collect::<Vec<i8>>();
-        if arr.len() < size as usize {
-            return Err(NbtParseError::EndOfData);
-        }
-        Ok(arr)
+impl<'a> Scanner<'a> {
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], NbtParseError> {
+        let end = self.pos.checked_add(len).ok_or(NbtParseError::EndOfData)?;
+        let bytes = self.data.get(self.pos..end).ok_or(NbtParseError::EndOfData)?;
+        self.pos = end;
+        Ok(bytes)
     }
 
-    fn next_string(iterable: &mut Peekable<Iter<u8>>) -> Result<String, NbtParseError> {
-        let size = next_short(iterable)?;
-        let name = String::from_utf8(iterable.take(size as usize).map(|n| *n).collect::<Vec<u8>>())?;
-        if name.as_bytes().len() < size as usize {
-            return Err(NbtParseError::EndOfData);
-        }
-        Ok(name)
-    }
-
-    fn next_list(iterable: &mut Peekable<Iter<u8>>) -> Result<Vec<NbtTag>, NbtParseError> {
-        let tag_id = next_byte(iterable)?;
-        let size = next_int(iterable)?;
-        let mut vec = vec![];
-        for _ in 0..size {
-            let value = match tag_id {
-                1 => NbtTag::Byte("".to_string(), next_byte(iterable)?),
-                2 => NbtTag::Short("".to_string(), next_short(iterable)?),
-                3 => NbtTag::Int("".to_string(), next_int(iterable)?),
-                4 => NbtTag::Long("".to_string(), next_long(iterable)?),
-                5 => NbtTag::Float("".to_string(), next_float(iterable)?),
-                6 => NbtTag::Double("".to_string(), next_double(iterable)?),
-                7 => NbtTag::ByteArray("".to_string(), next_byte_arr(iterable)?),
-                8 => NbtTag::String("".to_string(), next_string(iterable)?),
-                9 => NbtTag::List("".to_string(), next_list(iterable)?),
-                10 => NbtTag::Compound("".to_string(), next_compound(iterable)?),
-                11 => NbtTag::IntArray("".to_string(), next_int_arr(iterable)?),
-                12 => NbtTag::LongArray("".to_string(), next_long_arr(iterable)?),
-                _ => Err(NbtParseError::UnknownNBT(tag_id))?,
-            };
-            vec.push(value);
-        }
-        Ok(vec)
+    pub(crate) fn bytes<const N: usize>(&mut self) -> Result<[u8; N], NbtParseError> {
+        Ok(self.take(N)?.try_into().unwrap())
     }
 
-    fn next_compound(iterable: &mut Peekable<Iter<u8>>) -> Result<Vec<NbtTag>, NbtParseError> {
-        let mut vec = vec![];
-        loop {
-            let next = parse_next(iterable)?;
-            if next == NbtTag::End {
-                break;
-            }
-            vec.push(next);
-        }
-        Ok(vec)
+    pub(crate) fn type_id(&mut self) -> Result<i8, NbtParseError> {
+        Ok(self.take(1)?[0] as i8)
     }
 
-    fn next_int_arr(iterable: &mut Peekable<Iter<u8>>) -> Result<Vec<i32>, NbtParseError> {
-        let size = next_int(iterable)?;
-        let mut vec = vec![];
-        for _ in 0..size {
-            vec.push(next_int(iterable)?)
-        }
-        Ok(vec)
+    /// Reads the length of a list or array. Negative lengths are read as empty.
+    pub(crate) fn length(&mut self) -> Result<usize, NbtParseError> {
+        Ok(i32::from_be_bytes(self.bytes()?).max(0) as usize)
     }
 
-    fn next_long_arr(iterable: &mut Peekable<Iter<u8>>) -> Result<Vec<i64>, NbtParseError> {
-        let size = next_int(iterable)?;
-        let mut vec = vec![];
-        for _ in 0..size {
-            vec.push(next_long(iterable)?)
+    /// Reads the element type and length of a list. Unknown element types are an error even
+    /// in empty lists, which have no elements to fail on.
+    pub(crate) fn list_header(&mut self) -> Result<(i8, usize), NbtParseError> {
+        let element_type = self.type_id()?;
+        let len = self.length()?;
+        if !(0..=12).contains(&element_type) {
+            return Err(NbtParseError::UnknownNBT(element_type));
         }
-        Ok(vec)
-    }
-
-    pub(super) fn parse_byte(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        Ok(NbtTag::Byte(name, next_byte(iterable)?))
+        Ok((element_type, len))
     }
 
-    pub(super) fn parse_short(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        Ok(NbtTag::Short(name, next_short(iterable)?))
+    /// How many elements of a list to preallocate. The length comes from the data, so it's
+    /// bounded by how many elements the remaining bytes could hold. Compounds can be a single
+    /// byte each, so they aren't preallocated.
+    pub(crate) fn list_capacity(&self, element_type: i8, len: usize) -> usize {
+        let min_size = match element_type {
+            1 => 1,
+            2 | 8 => 2,
+            3 | 5 | 7 | 11 | 12 => 4,
+            4 | 6 => 8,
+            9 => 5,
+            _ => return 0,
+        };
+        len.min((self.data.len() - self.pos) / min_size)
     }
 
-    pub(super) fn parse_int(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        Ok(NbtTag::Int(name, next_int(iterable)?))
+    /// Reads a string. Invalid UTF-8 is a `StringUtf8Error`, like in owned strings.
+    pub(crate) fn string(&mut self) -> Result<&'a str, NbtParseError> {
+        let len = u16::from_be_bytes(self.bytes()?) as usize;
+        let bytes = self.take(len)?;
+        std::str::from_utf8(bytes).map_err(|_| String::from_utf8(bytes.to_vec()).unwrap_err().into())
     }
 
-    pub(super) fn parse_long(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        Ok(NbtTag::Long(name, next_long(iterable)?))
+    pub(crate) fn array(&mut self, element_type: i8, width: usize) -> Result<NbtArray<'a>, NbtParseError> {
+        let len = self.length()?.checked_mul(width).ok_or(NbtParseError::EndOfData)?;
+        Ok(NbtArray { element_type, data: self.take(len)? })
     }
 
-    pub(super) fn parse_float(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        Ok(NbtTag::Float(name, next_float(iterable)?))
+    fn owned_string(&mut self) -> Result<String, NbtParseError> {
+        Ok(self.string()?.to_string())
     }
 
-    pub(super) fn parse_double(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        Ok(NbtTag::Double(name, next_double(iterable)?))
+    fn named(&mut self) -> Result<NbtTag, NbtParseError> {
+        let id = self.type_id()?;
+        if id == 0 {
+            return Ok(NbtTag::End);
+        }
+        let name = self.owned_string()?;
+        self.payload(id, name)
+    }
+
+    fn payload(&mut self, id: i8, name: String) -> Result<NbtTag, NbtParseError> {
+        Ok(match id {
+            1 => NbtTag::Byte(name, self.type_id()?),
+            2 => NbtTag::Short(name, i16::from_be_bytes(self.bytes()?)),
+            3 => NbtTag::Int(name, i32::from_be_bytes(self.bytes()?)),
+            4 => NbtTag::Long(name, i64::from_be_bytes(self.bytes()?)),
+            5 => NbtTag::Float(name, f32::from_be_bytes(self.bytes()?)),
+            6 => NbtTag::Double(name, f64::from_be_bytes(self.bytes()?)),
+            7 => NbtTag::ByteArray(name, decode_bytes(self.array(7, 1)?.data)),
+            8 => NbtTag::String(name, self.owned_string()?),
+            9 => NbtTag::List(name, self.list()?),
+            10 => NbtTag::Compound(name, self.compound()?),
+            11 => NbtTag::IntArray(name, decode_ints(self.array(11, 4)?.data)),
+            12 => NbtTag::LongArray(name, decode_longs(self.array(12, 8)?.data)),
+            id => return Err(NbtParseError::UnknownNBT(id)),
+        })
+    }
+
+    /// Reads a list. Numbers are decoded in bulk, other elements one by one.
+    fn list(&mut self) -> Result<NbtList, NbtParseError> {
+        let (element_type, len) = self.list_header()?;
+        Ok(match element_type {
+            1 => NbtList::Byte(decode_bytes(self.numbers(len, 1)?)),
+            2 => NbtList::Short(decode(self.numbers(len, 2)?, i16::from_be_bytes)),
+            3 => NbtList::Int(decode_ints(self.numbers(len, 4)?)),
+            4 => NbtList::Long(decode_longs(self.numbers(len, 8)?)),
+            5 => NbtList::Float(decode(self.numbers(len, 4)?, f32::from_be_bytes)),
+            6 => NbtList::Double(decode(self.numbers(len, 8)?, f64::from_be_bytes)),
+            _ => {
+                let mut list = NbtList::with_capacity(element_type, self.list_capacity(element_type, len));
+                for _ in 0..len {
+                    list.push(self.payload(element_type, String::new())?)?;
+                }
+                list
+            }
+        })
     }
 
-    pub(super) fn parse_byte_arr(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        Ok(NbtTag::ByteArray(name, next_byte_arr(iterable)?))
+    fn numbers(&mut self, len: usize, width: usize) -> Result<&'a [u8], NbtParseError> {
+        self.take(len.checked_mul(width).ok_or(NbtParseError::EndOfData)?)
     }
 
-    pub(super) fn parse_string(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        let string = next_string(iterable)?;
-        Ok(NbtTag::String(name, string))
+    fn compound(&mut self) -> Result<NbtCompound, NbtParseError> {
+        let mut compound = NbtCompound::new();
+        loop {
+            match self.named()? {
+                NbtTag::End => return Ok(compound),
+                tag => compound.push_unique(tag)?,
+            }
+        }
     }
+}
 
-    pub(super) fn parse_list(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        let vec = next_list(iterable)?;
-        Ok(NbtTag::List(name, vec))
-    }
+pub(crate) fn decode_bytes(data: &[u8]) -> Vec<i8> {
+    data.iter().map(|byte| *byte as i8).collect()
+}
 
-    pub(super) fn parse_compound(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        let vec = next_compound(iterable)?;
-        Ok(NbtTag::Compound(name, vec))
-    }
+pub(crate) fn decode_ints(data: &[u8]) -> Vec<i32> {
+    decode(data, i32::from_be_bytes)
+}
 
-    pub(super) fn parse_int_array(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        let vec = next_int_arr(iterable)?;
-        Ok(NbtTag::IntArray(name, vec))
-    }
+pub(crate) fn decode_longs(data: &[u8]) -> Vec<i64> {
+    decode(data, i64::from_be_bytes)
+}
 
-    pub(super) fn parse_long_array(iterable: &mut Peekable<Iter<u8>>) -> Result<NbtTag, NbtParseError> {
-        let name = next_string(iterable)?;
-        let vec = next_long_arr(iterable)?;
-        Ok(NbtTag::LongArray(name, vec))
-    }
+fn decode<T, const N: usize>(data: &[u8], from_bytes: fn([u8; N]) -> T) -> Vec<T> {
+    data.chunks_exact(N).map(|bytes| from_bytes(bytes.try_into().unwrap())).collect()
 }
 
 #[cfg(test)]
+#[allow(clippy::char_lit_as_u8, clippy::excessive_precision)]
 mod tests {
     use super::*;
 
     #[test]
     fn parse_byte() {
-        let tree = nbt_parser::parse_binary(vec![1, 0, 4, 'b' as u8, 'y' as u8, 't' as u8, 'e' as u8, 0xcc]);
+        let tree = nbt_parser::parse_binary(vec![1, 0, 4, 'b' as u8, 'y' as u8, 't' as u8, 'e' as u8, 0xcc]).unwrap();
         assert_eq!(tree, NbtTag::Byte("byte".to_string(), 0xccu8 as i8));
     }
 
     #[test]
     fn parse_short() {
-        let tree = nbt_parser::parse_binary(vec![2, 0, 5, 's' as u8, 'h' as u8, 'o' as u8, 'r' as u8, 't' as u8, 0xde, 0xad]);
+        let tree = nbt_parser::parse_binary(vec![2, 0, 5, 's' as u8, 'h' as u8, 'o' as u8, 'r' as u8, 't' as u8, 0xde, 0xad]).unwrap();
         assert_eq!(tree, NbtTag::Short("short".to_string(), 0xdeadu16 as i16));
     }
 
     #[test]
     fn parse_int() {
-        let tree = nbt_parser::parse_binary(vec![3, 0, 3, 'i' as u8, 'n' as u8, 't' as u8, 0xde, 0xad, 0xbe, 0xef]);
+        let tree = nbt_parser::parse_binary(vec![3, 0, 3, 'i' as u8, 'n' as u8, 't' as u8, 0xde, 0xad, 0xbe, 0xef]).unwrap();
         assert_eq!(tree, NbtTag::Int("int".to_string(), 0xdeadbeefu32 as i32));
     }
 
     #[test]
     fn parse_long() {
-        let tree = nbt_parser::parse_binary(vec![4, 0, 4, 'l' as u8, 'o' as u8, 'n' as u8, 'g' as u8, 0xde, 0xad, 0xbe, 0xef, 0xde, 0xad, 0xbe, 0xef]);
+        let tree = nbt_parser::parse_binary(vec![4, 0, 4, 'l' as u8, 'o' as u8, 'n' as u8, 'g' as u8, 0xde, 0xad, 0xbe, 0xef, 0xde, 0xad, 0xbe, 0xef]).unwrap();
         assert_eq!(tree, NbtTag::Long("long".to_string(), 0xdeadbeefdeadbeefu64 as i64));
     }
 
     #[test]
     fn parse_float() {
-        let tree = nbt_parser::parse_binary(vec![5, 0, 5, b'f', b'l', b'o', b'a', b't', 0x46, 0x4f, 0x16, 0x00]);
+        let tree = nbt_parser::parse_binary(vec![5, 0, 5, b'f', b'l', b'o', b'a', b't', 0x46, 0x4f, 0x16, 0x00]).unwrap();
         assert_eq!(tree, NbtTag::Float("float".to_string(), 13253.5_f32));
     }
 
     #[test]
     fn parse_double() {
-        let tree = nbt_parser::parse_binary(vec![6, 0, 6, b'd', b'o', b'u', b'b', b'l', b'e', 0x5f, 0xbc, 0xe6, 0x7f, 0xb6, 0x5a, 0xfb, 0x65]);
+        let tree = nbt_parser::parse_binary(vec![6, 0, 6, b'd', b'o', b'u', b'b', b'l', b'e', 0x5f, 0xbc, 0xe6, 0x7f, 0xb6, 0x5a, 0xfb, 0x65]).unwrap();
         assert_eq!(tree, NbtTag::Double("double".to_string(), 1.51363604588254730582137744226e153));
     }
 
     #[test]
     fn parse_byte_array() {
-        let tree = nbt_parser::parse_binary(vec![7, 0, 7, 'b' as u8, 'y' as u8, 't' as u8, 'e' as u8, 'a' as u8, 'r' as u8, 'r' as u8, 0, 0, 0, 4, 0xde, 0xad, 0xbe, 0xef]);
+        let tree = nbt_parser::parse_binary(vec![7, 0, 7, 'b' as u8, 'y' as u8, 't' as u8, 'e' as u8, 'a' as u8, 'r' as u8, 'r' as u8, 0, 0, 0, 4, 0xde, 0xad, 0xbe, 0xef]).unwrap();
         assert_eq!(tree, NbtTag::ByteArray("bytearr".to_string(), vec![0xde_u8 as i8, 0xad_u8 as i8, 0xbe_u8 as i8, 0xef_u8 as i8]));
     }
 
     #[test]
     fn parse_string() {
-        let tree = nbt_parser::parse_binary(vec![8, 0, 7, 's' as u8, 't' as u8, 'r' as u8, 'i' as u8, 'n' as u8, 'g' as u8, '1' as u8, 0, 7, 's' as u8, 't' as u8, 'r' as u8, 'i' as u8, 'n' as u8, 'g' as u8, '2' as u8]);
+        let tree = nbt_parser::parse_binary(vec![8, 0, 7, 's' as u8, 't' as u8, 'r' as u8, 'i' as u8, 'n' as u8, 'g' as u8, '1' as u8, 0, 7, 's' as u8, 't' as u8, 'r' as u8, 'i' as u8, 'n' as u8, 'g' as u8, '2' as u8]).unwrap();
         assert_eq!(tree, NbtTag::String("string1".to_string(), "string2".to_string()));
     }
 
@@ -300,14 +297,14 @@ mod tests {
             0x55, 0x66,
             0x77, 0x88,
             0x99, 0xaa,
-        ]);
+        ]).unwrap();
         assert_eq!(tree, NbtTag::List("list".to_string(), vec![
             NbtTag::Short("".to_string(), 0x1122),
             NbtTag::Short("".to_string(), 0x3344),
             NbtTag::Short("".to_string(), 0x5566),
             NbtTag::Short("".to_string(), 0x7788),
             NbtTag::Short("".to_string(), 0x99aa_u16 as i16),
-        ]));
+        ].try_into().unwrap()));
     }
 
     #[test]
@@ -325,12 +322,61 @@ mod tests {
             8, 0, 3, b'l', b'o', b'l', 0, 4, b'l', b'l', b'l', b'l',
             // END tag
             0x00,
-        ]);
+        ]).unwrap();
         assert_eq!(tree, NbtTag::Compound("comp".to_string(), vec![
             NbtTag::Byte("".to_string(), 0x22),
             NbtTag::Short("hi".to_string(), 0x2233),
             NbtTag::String("lol".to_string(), "llll".to_string()),
-        ]));
+        ].into()));
+    }
+
+    #[test]
+    fn parse_duplicate_key() {
+        let tree = nbt_parser::parse_binary(vec![
+            10, 0, 0,
+            1, 0, 1, b'a', 0x22,
+            1, 0, 1, b'a', 0x33,
+            0x00,
+        ]);
+        assert!(matches!(tree, Err(NbtParseError::DuplicateKey(name)) if name == "a"));
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let tree = nbt_parser::parse_binary(vec![3, 0, 3, b'i', b'n', b't', 0xde, 0xad]);
+        assert!(matches!(tree, Err(NbtParseError::EndOfData)));
+    }
+
+    #[test]
+    fn parse_invalid_values() {
+        // Every parser reports invalid UTF-8 the same way
+        let data = vec![10, 0, 0, 8, 0, 1, b's', 0, 2, 0xc3, 0x28, 0];
+        assert!(matches!(nbt_parser::parse_binary(data.clone()), Err(NbtParseError::StringUtf8Error(_))));
+        assert!(matches!(nbt_parser::parse_borrowed(&data), Err(NbtParseError::StringUtf8Error(_))));
+        let path = "s".parse().unwrap();
+        assert!(matches!(crate::nbt_select::parse_paths(&data, &[path]), Err(NbtParseError::StringUtf8Error(_))));
+
+        // Lists must have a known element type, even when empty
+        let data = vec![9, 0, 0, 13, 0, 0, 0, 0];
+        assert!(matches!(nbt_parser::parse_binary(data.clone()), Err(NbtParseError::UnknownNBT(13))));
+        assert!(matches!(nbt_parser::parse_borrowed(&data), Err(NbtParseError::UnknownNBT(13))));
+
+        // Negative lengths of lists and arrays are read as empty
+        for id in [7, 9, 11, 12] {
+            let mut data = vec![id, 0, 0];
+            if id == 9 {
+                data.push(1);
+            }
+            data.extend_from_slice(&(-1i32).to_be_bytes());
+            let empty = match nbt_parser::parse_binary(data).unwrap() {
+                NbtTag::ByteArray(_, array) => array.is_empty(),
+                NbtTag::List(_, list) => list.is_empty(),
+                NbtTag::IntArray(_, array) => array.is_empty(),
+                NbtTag::LongArray(_, array) => array.is_empty(),
+                _ => false,
+            };
+            assert!(empty);
+        }
     }
 
     #[test]
@@ -340,7 +386,7 @@ mod tests {
             // size
             0, 0, 0, 2,
             0, 0, 0, 4, 0xde, 0xad, 0xbe, 0xef
-        ]);
+        ]).unwrap();
         assert_eq!(tree, NbtTag::IntArray("intarr".to_string(), vec![4, 0xdeadbeef_u32 as i32]));
     }
 
@@ -353,11 +399,38 @@ mod tests {
             0, 0, 0, 4, 0xde, 0xad, 0xbe, 0xef,
             1, 2, 3, 4, 5, 6, 7, 8,
             9, 10, 11, 12, 13, 14, 15, 16,
-        ]);
+        ]).unwrap();
         assert_eq!(tree, NbtTag::LongArray("longarr".to_string(), vec![
             0x4deadbeef,
             0x0102030405060708,
             0x090a0b0c0d0e0f10,
         ]));
     }
+
+    #[test]
+    fn parse_large_values() {
+        let string = "a".repeat(40000);
+        let longs: Vec<i64> = (0..4096).map(|n| n * 0x0101_0101_0101).collect();
+        let mut data = vec![10, 0, 0, 8, 0, 1, b's'];
+        data.extend_from_slice(&(string.len() as u16).to_be_bytes());
+        data.extend_from_slice(string.as_bytes());
+        data.extend_from_slice(&[12, 0, 1, b'l']);
+        data.extend_from_slice(&(longs.len() as i32).to_be_bytes());
+        data.extend(longs.iter().flat_map(|n| n.to_be_bytes()));
+        data.push(0);
+        let tree = nbt_parser::parse_binary(data.clone()).unwrap();
+        assert_eq!(tree.get("s").unwrap(), NbtTag::String("s".to_string(), string));
+        assert_eq!(tree.get("l").unwrap(), NbtTag::LongArray("l".to_string(), longs));
+        assert!(matches!(nbt_parser::parse_binary(data[..data.len() - 2].to_vec()), Err(NbtParseError::EndOfData)));
+    }
+
+    #[test]
+    fn bound_list_capacity() {
+        let scanner = Scanner { data: &[0; 40], pos: 0 };
+        assert_eq!(scanner.list_capacity(1, 1000), 40);
+        assert_eq!(scanner.list_capacity(4, 1000), 5);
+        assert_eq!(scanner.list_capacity(9, 1000), 8);
+        assert_eq!(scanner.list_capacity(8, 3), 3);
+        assert_eq!(scanner.list_capacity(10, 1000), 0);
+    }
 }
@@ -0,0 +1,604 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::NbtParseError;
+use crate::NbtParseError::{InvalidValue, NoSuchValue, WrongType};
+use crate::compound::NbtCompound;
+use crate::list::{type_name, NbtList};
+use crate::select::{PathStep, Selection};
+use crate::snbt::{snbt_writer, SnbtReader};
+use crate::types::NbtTag;
+use crate::value::{NbtValue, NbtValueMut};
+
+/// A path to values in a tree, in the syntax of the `/data` command, e.g.
+/// `Inventory[{Slot:0b}].components."minecraft:custom_name"` or `Data.Player.Pos[1]`.
+///
+/// Paths consist of compound children (`name`, `"quoted name"`, optionally followed by a
+/// filter like `name{id:"minecraft:stone"}`) and list elements (`[0]`, `[-1]` counting from
+/// the end, `[]` for all elements and `[{Slot:0b}]` for the elements matching a filter).
+/// A path may start with a filter on the root, like `{Data:{}}`.
+///
+/// A filter matches a compound containing all of its children. Nested compounds are matched
+/// the same way, and a list in a filter matches a list containing all of its elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtPath {
+    source: String,
+    nodes: Vec<PathNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathNode {
+    Root(NbtCompound),
+    Named(String),
+    NamedFilter(String, NbtCompound),
+    Index(i32),
+    All,
+    Filter(NbtCompound),
+}
+
+impl NbtPath {
+    /// The empty path, pointing at the root itself.
+    pub(crate) fn root() -> NbtPath {
+        NbtPath { source: String::new(), nodes: vec![] }
+    }
+
+    /// Extends the path with a compound child.
+    pub(crate) fn child(&self, name: &str) -> NbtPath {
+        let quoted = if !name.is_empty() && name.chars().all(is_name_char) { name.to_string() } else { snbt_writer::quote(name) };
+        let source = if self.source.is_empty() { quoted } else { format!("{}.{quoted}", self.source) };
+        self.with_node(source, PathNode::Named(name.to_string()))
+    }
+
+    /// Extends the path with a list index.
+    pub(crate) fn index(&self, index: i32) -> NbtPath {
+        self.with_node(format!("{}[{index}]", self.source), PathNode::Index(index))
+    }
+
+    /// Extends the path with the list elements matching a filter.
+    pub(crate) fn filtered(&self, filter: NbtCompound) -> NbtPath {
+        let snbt = snbt_writer::to_snbt(&NbtTag::Compound(String::new(), filter.clone()));
+        self.with_node(format!("{}[{snbt}]", self.source), PathNode::Filter(filter))
+    }
+
+    fn with_node(&self, source: String, node: PathNode) -> NbtPath {
+        let mut nodes = self.nodes.clone();
+        nodes.push(node);
+        NbtPath { source, nodes }
+    }
+
+    /// Returns all values the path points to, failing if there are none.
+    pub fn get<'a>(&self, root: &'a NbtTag) -> Result<Vec<NbtValue<'a>>, NbtParseError> {
+        let mut values: Vec<_> = root.as_value().into_iter().collect();
+        for node in &self.nodes {
+            values = values.into_iter().flat_map(|value| node.select(value)).collect();
+        }
+        if values.is_empty() {
+            return Err(NoSuchValue(self.source.clone()));
+        }
+        Ok(values)
+    }
+
+    /// Returns the number of values the path points to.
+    pub fn count(&self, root: &NbtTag) -> usize {
+        self.get(root).map_or(0, |values| values.len())
+    }
+
+    /// Replaces all values the path points to, or adds the value to a compound when it
+    /// doesn't exist yet. Missing compounds and lists along the path are created.
+    /// Returns the number of values that were set, failing if there were none. The tree is
+    /// left unchanged when it fails.
+    pub fn set(&self, root: &mut NbtTag, value: NbtTag) -> Result<usize, NbtParseError> {
+        self.check(root, &value, false)?;
+        let Some((last, parents)) = self.nodes.split_last() else {
+            return Err(NoSuchValue(self.source.clone()));
+        };
+        let mut count = 0;
+        if let PathNode::Root(filter) = last {
+            // The root filter is always the first node, so this path points at the root
+            if root.as_value().is_some_and(|tag| compound_matches(filter, tag)) {
+                let name = root.get_name();
+                *root = value;
+                root.set_name(name);
+                count = 1;
+            }
+        } else {
+            for tag in self.select_parents(root, parents) {
+                count += last.set(tag, &value)?;
+            }
+        }
+        if count == 0 {
+            return Err(NoSuchValue(self.source.clone()));
+        }
+        Ok(count)
+    }
+
+    /// Like [`NbtPath::set`], except that a path ending in a list element inserts the value
+    /// into the list. `[index]` inserts before the element at the index, with `[-1]`
+    /// appending, while `[]` and filters like `[{Slot:0b}]` append the value.
+    pub fn insert(&self, root: &mut NbtTag, value: NbtTag) -> Result<usize, NbtParseError> {
+        let Some((last, parents)) = self.nodes.split_last() else {
+            return Err(NoSuchValue(self.source.clone()));
+        };
+        if matches!(last, PathNode::Root(_)) {
+            return self.set(root, value);
+        }
+        self.check(root, &value, true)?;
+        let mut count = 0;
+        for tag in self.select_parents(root, parents) {
+            count += match (last, tag) {
+                (PathNode::Index(index), NbtValueMut::List(list)) => {
+                    let Some(index) = insert_index(*index, list.len()) else {
+                        continue;
+                    };
+                    list.insert(index, value.clone())?;
+                    1
+                }
+                (PathNode::All | PathNode::Filter(_), NbtValueMut::List(list)) => {
+                    list.push(value.clone())?;
+                    1
+                }
+                (last, tag) => last.set(tag, &value)?,
+            };
+        }
+        if count == 0 {
+            return Err(NoSuchValue(self.source.clone()));
+        }
+        Ok(count)
+    }
+
+    /// Checks that setting or inserting `value` changes at least one value and can't fail,
+    /// without changing the tree. Missing compounds along the path count as the empty
+    /// compounds and lists that would be created for them.
+    fn check(&self, root: &NbtTag, value: &NbtTag, insert: bool) -> Result<(), NbtParseError> {
+        if *value == NbtTag::End {
+            return Err(InvalidValue("end tags can't be set".to_string()));
+        }
+        let Some((last, parents)) = self.nodes.split_last() else {
+            return Err(NoSuchValue(self.source.clone()));
+        };
+        let (empty_compound, empty_list) = (NbtCompound::new(), NbtList::default());
+        let mut targets: Vec<_> = root.as_value().into_iter().collect();
+        for (node, next) in parents.iter().zip(&self.nodes[1..]) {
+            targets = targets.into_iter().flat_map(|target| match (node, target) {
+                (PathNode::Named(name), NbtValue::Compound(compound)) if !compound.contains_key(name) => {
+                    vec![if next.holds_list() { NbtValue::List(&empty_list) } else { NbtValue::Compound(&empty_compound) }]
+                }
+                (PathNode::NamedFilter(name, filter), NbtValue::Compound(compound)) if !compound.contains_key(name) => {
+                    vec![NbtValue::Compound(filter)]
+                }
+                (node, target) => node.select(target),
+            }).collect();
+        }
+        let mut count = 0;
+        for target in targets {
+            count += last.check(target, value, insert)?;
+        }
+        if count == 0 {
+            return Err(NoSuchValue(self.source.clone()));
+        }
+        Ok(())
+    }
+
+    /// Selects the values `parents` lead to, creating missing compound children along the
+    /// way.
+    fn select_parents<'a>(&self, root: &'a mut NbtTag, parents: &[PathNode]) -> Vec<NbtValueMut<'a>> {
+        let mut tags: Vec<_> = root.as_value_mut().into_iter().collect();
+        for (index, node) in parents.iter().enumerate() {
+            let next = self.nodes.get(index + 1);
+            tags = tags.into_iter().flat_map(|tag| node.select_mut(tag, next)).collect();
+        }
+        tags
+    }
+
+    /// Whether this is the empty path, pointing at the root itself.
+    pub(crate) fn is_root(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// How selective parsing treats the tag at `steps`. Filters and negative indices can't be
+    /// checked before the value is read, so they match every candidate, and the children a
+    /// filter checks are kept so the path can still be evaluated on the result. Positive
+    /// indices match every candidate too, so the elements before them keep their place.
+    pub(crate) fn selection(&self, steps: &[PathStep]) -> Selection {
+        let mut filter = match self.nodes.first() {
+            Some(PathNode::Root(filter)) => Some(filter),
+            _ => None,
+        };
+        let nodes = if filter.is_some() { &self.nodes[1..] } else { &self.nodes[..] };
+        for (index, step) in steps.iter().enumerate() {
+            let Some(node) = nodes.get(index) else {
+                return Selection::Keep;
+            };
+            let matches = match (node, step) {
+                (PathNode::Named(name) | PathNode::NamedFilter(name, _), PathStep::Name(step)) => name == step,
+                (PathNode::Index(_) | PathNode::All | PathNode::Filter(_), PathStep::Index(_)) => true,
+                _ => false,
+            };
+            let checked = || matches!((filter, step), (Some(filter), PathStep::Name(name)) if filter.get(name).is_some());
+            if !matches {
+                return if checked() { Selection::Keep } else { Selection::Skip };
+            }
+            filter = match node {
+                PathNode::NamedFilter(_, filter) | PathNode::Filter(filter) => Some(filter),
+                _ => None,
+            };
+        }
+        if steps.len() >= nodes.len() { Selection::Keep } else { Selection::Descend }
+    }
+
+    /// Removes all values the path points to and returns how many were removed. The root
+    /// itself can't be removed.
+    pub fn remove(&self, root: &mut NbtTag) -> usize {
+        let Some((last, parents)) = self.nodes.split_last() else {
+            return 0;
+        };
+        let mut tags: Vec<_> = root.as_value_mut().into_iter().collect();
+        for node in parents {
+            tags = tags.into_iter().flat_map(|tag| node.select_mut(tag, None)).collect();
+        }
+        tags.into_iter().map(|tag| last.remove(tag)).sum()
+    }
+}
+
+impl PathNode {
+    fn select<'a>(&self, value: NbtValue<'a>) -> Vec<NbtValue<'a>> {
+        match (self, value) {
+            (PathNode::Root(filter), value) => [value].into_iter().filter(|value| compound_matches(filter, *value)).collect(),
+            (PathNode::Named(name), NbtValue::Compound(compound)) => compound.get(name).and_then(NbtTag::as_value).into_iter().collect(),
+            (PathNode::NamedFilter(name, filter), NbtValue::Compound(compound)) => {
+                compound.get(name).and_then(NbtTag::as_value).into_iter().filter(|value| compound_matches(filter, *value)).collect()
+            }
+            (PathNode::Index(index), NbtValue::List(list)) => {
+                resolve_index(*index, list.len()).and_then(|index| list.get(index)).into_iter().collect()
+            }
+            (PathNode::All, NbtValue::List(list)) => list.iter().collect(),
+            (PathNode::Filter(filter), NbtValue::List(list)) => {
+                list.iter().filter(|value| compound_matches(filter, *value)).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Like `select`, but creates missing compound children when `next` is given. The
+    /// created child is a list or compound, depending on the node that follows.
+    fn select_mut<'a>(&self, tag: NbtValueMut<'a>, next: Option<&PathNode>) -> Vec<NbtValueMut<'a>> {
+        match (self, tag) {
+            (PathNode::Root(filter), tag) => {
+                if compound_matches(filter, tag.as_value()) { vec![tag] } else { vec![] }
+            }
+            (PathNode::Named(name), NbtValueMut::Compound(compound)) => {
+                if let Some(next) = next {
+                    if !compound.contains_key(name) {
+                        compound.insert(next.empty_parent(name)).expect("empty parents are lists or compounds");
+                    }
+                }
+                compound.get_mut(name).into_iter().collect()
+            }
+            (PathNode::NamedFilter(name, filter), NbtValueMut::Compound(compound)) => {
+                if next.is_some() && !compound.contains_key(name) {
+                    compound.insert(NbtTag::Compound(name.clone(), filter.clone())).expect("filters are compounds");
+                }
+                compound.get_mut(name).into_iter().filter(|tag| compound_matches(filter, tag.as_value())).collect()
+            }
+            (PathNode::Index(index), NbtValueMut::List(list)) => {
+                let index = resolve_index(*index, list.len());
+                index.and_then(|index| list.get_mut(index)).into_iter().collect()
+            }
+            (PathNode::All, NbtValueMut::List(list)) => list.iter_mut().collect(),
+            (PathNode::Filter(filter), NbtValueMut::List(list)) => {
+                list.iter_mut().filter(|value| compound_matches(filter, value.as_value())).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Whether this node selects list elements, so a missing child it is applied to is
+    /// created as a list.
+    fn holds_list(&self) -> bool {
+        matches!(self, PathNode::Index(_) | PathNode::All | PathNode::Filter(_))
+    }
+
+    /// The empty tag to create for a missing child that this node is applied to next.
+    fn empty_parent(&self, name: &str) -> NbtTag {
+        if self.holds_list() {
+            NbtTag::List(name.to_string(), NbtList::default())
+        } else {
+            NbtTag::Compound(name.to_string(), NbtCompound::new())
+        }
+    }
+
+    /// The number of values `set`, or `insert` if `insert` is true, changes in `target`, or
+    /// the error it fails with.
+    fn check(&self, target: NbtValue<'_>, value: &NbtTag, insert: bool) -> Result<usize, NbtParseError> {
+        Ok(match (self, target) {
+            (PathNode::Root(filter), target) => compound_matches(filter, target) as usize,
+            (PathNode::Named(_), NbtValue::Compound(_)) => 1,
+            (PathNode::NamedFilter(name, filter), NbtValue::Compound(compound)) => {
+                compound.get(name).is_some_and(|tag| tag_matches(filter, tag)) as usize
+            }
+            (PathNode::Index(index), NbtValue::List(list)) if insert => {
+                match insert_index(*index, list.len()) {
+                    Some(_) => check_element(list, value).map(|_| 1)?,
+                    None => 0,
+                }
+            }
+            (PathNode::All | PathNode::Filter(_), NbtValue::List(list)) if insert => check_element(list, value).map(|_| 1)?,
+            (node, NbtValue::List(list)) => {
+                let count = node.select(target).len();
+                if count > 0 {
+                    check_element(list, value)?;
+                }
+                count
+            }
+            _ => 0,
+        })
+    }
+
+    fn set(&self, tag: NbtValueMut<'_>, value: &NbtTag) -> Result<usize, NbtParseError> {
+        match (self, tag) {
+            (PathNode::Named(name), NbtValueMut::Compound(compound)) => {
+                let mut value = value.clone();
+                value.set_name(name.as_str());
+                compound.insert(value)?;
+                Ok(1)
+            }
+            (PathNode::NamedFilter(name, filter), NbtValueMut::Compound(compound)) => {
+                if !compound.get(name).is_some_and(|tag| tag_matches(filter, tag)) {
+                    return Ok(0);
+                }
+                let mut value = value.clone();
+                value.set_name(name.as_str());
+                compound.insert(value)?;
+                Ok(1)
+            }
+            (PathNode::Index(index), NbtValueMut::List(list)) => {
+                let resolved = resolve_index(*index, list.len());
+                replace_elements(list, value, |i, _| Some(i) == resolved)
+            }
+            (PathNode::All, NbtValueMut::List(list)) => replace_elements(list, value, |_, _| true),
+            (PathNode::Filter(filter), NbtValueMut::List(list)) => {
+                replace_elements(list, value, |_, element| compound_matches(filter, element))
+            }
+            _ => Ok(0),
+        }
+    }
+
+    fn remove(&self, tag: NbtValueMut<'_>) -> usize {
+        match (self, tag) {
+            (PathNode::Named(name), NbtValueMut::Compound(compound)) => compound.remove(name).map_or(0, |_| 1),
+            (PathNode::NamedFilter(name, filter), NbtValueMut::Compound(compound))
+                if compound.get(name).is_some_and(|tag| tag_matches(filter, tag)) => {
+                compound.remove(name);
+                1
+            }
+            (PathNode::Index(index), NbtValueMut::List(list)) => {
+                resolve_index(*index, list.len()).and_then(|index| list.remove(index)).map_or(0, |_| 1)
+            }
+            (PathNode::All, NbtValueMut::List(list)) => {
+                let len = list.len();
+                list.retain(|_| false);
+                len
+            }
+            (PathNode::Filter(filter), NbtValueMut::List(list)) => {
+                let len = list.len();
+                list.retain(|element| !compound_matches(filter, element));
+                len - list.len()
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// Replaces the selected elements of a list, which must have the list's element type.
+fn replace_elements<F: Fn(usize, NbtValue<'_>) -> bool>(list: &mut NbtList, value: &NbtTag, selected: F) -> Result<usize, NbtParseError> {
+    let indices: Vec<usize> = list.iter().enumerate()
+        .filter(|(index, element)| selected(*index, *element))
+        .map(|(index, _)| index)
+        .collect();
+    for index in &indices {
+        list.set(*index, value.clone())?;
+    }
+    Ok(indices.len())
+}
+
+/// Checks that `value` can be stored in `list`.
+fn check_element(list: &NbtList, value: &NbtTag) -> Result<(), NbtParseError> {
+    match list.element_type() {
+        0 => Ok(()),
+        element_type if element_type == value.type_id() => Ok(()),
+        element_type => Err(WrongType(type_name(element_type), value.type_name())),
+    }
+}
+
+/// The position to insert at for an index of an insert path, where `-1` appends.
+fn insert_index(index: i32, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let index = if index < 0 { len + index as i64 + 1 } else { index as i64 };
+    (0..=len).contains(&index).then_some(index as usize)
+}
+
+fn resolve_index(index: i32, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index as i64 } else { index as i64 };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+fn compound_matches(filter: &NbtCompound, value: NbtValue<'_>) -> bool {
+    match value {
+        NbtValue::Compound(compound) => filter.iter().all(|child| {
+            let value = compound.get(child.name()).and_then(NbtTag::as_value);
+            child.as_value().zip(value).is_some_and(|(filter, value)| matches(filter, value))
+        }),
+        _ => false,
+    }
+}
+
+fn tag_matches(filter: &NbtCompound, tag: &NbtTag) -> bool {
+    tag.as_value().is_some_and(|value| compound_matches(filter, value))
+}
+
+/// Compares a filter value with a value.
+fn matches(filter: NbtValue<'_>, value: NbtValue<'_>) -> bool {
+    match (filter, value) {
+        (NbtValue::Compound(filter), value) => compound_matches(filter, value),
+        (NbtValue::List(filter), NbtValue::List(list)) => {
+            if filter.is_empty() {
+                list.is_empty()
+            } else {
+                filter.iter().all(|filter| list.iter().any(|value| matches(filter, value)))
+            }
+        }
+        (filter, value) => filter == value,
+    }
+}
+
+/// Characters that can't appear in unquoted names.
+fn is_name_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '"' | '\'' | '[' | ']' | '{' | '}' | '.')
+}
+
+impl FromStr for NbtPath {
+    type Err = NbtParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut reader = SnbtReader::new(s);
+        let mut nodes = vec![];
+        if reader.peek() == Some('{') {
+            nodes.push(PathNode::Root(reader.read_compound()?));
+        }
+        loop {
+            match reader.peek() {
+                None if !nodes.is_empty() => break,
+                Some('[') => {
+                    reader.next();
+                    let node = match reader.peek() {
+                        Some(']') => PathNode::All,
+                        Some('{') => PathNode::Filter(reader.read_compound()?),
+                        _ => {
+                            let index = reader.read_while(|c| c == '-' || c.is_ascii_digit());
+                            PathNode::Index(index.parse().map_err(|_| reader.error("expected an index"))?)
+                        }
+                    };
+                    reader.expect(']')?;
+                    nodes.push(node);
+                }
+                _ => {
+                    if !nodes.is_empty() {
+                        reader.expect('.')?;
+                    }
+                    let name = match reader.peek() {
+                        Some('"' | '\'') => reader.read_quoted()?,
+                        _ => reader.read_while(is_name_char).to_string(),
+                    };
+                    if name.is_empty() {
+                        return Err(reader.error("expected a name"));
+                    }
+                    if reader.peek() == Some('{') {
+                        nodes.push(PathNode::NamedFilter(name, reader.read_compound()?));
+                    } else {
+                        nodes.push(PathNode::Named(name));
+                    }
+                }
+            }
+        }
+        Ok(NbtPath { source: s.to_string(), nodes })
+    }
+}
+
+impl Display for NbtPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NbtParseError::WrongType;
+    use crate::snbt_parser::parse_snbt;
+
+    fn player() -> NbtTag {
+        parse_snbt(r#"{
+            Pos: [1.0d, 64.0d, -3.5d],
+            Inventory: [
+                {Slot: 0b, id: "minecraft:stone", count: 1, components: {"minecraft:custom_name": "Rock"}},
+                {Slot: 1b, id: "minecraft:dirt", count: 3},
+                {Slot: 2b, id: "minecraft:stone", count: 5}
+            ]
+        }"#).unwrap()
+    }
+
+    #[test]
+    fn parse_paths() {
+        assert!("a.b[0]".parse::<NbtPath>().is_ok());
+        assert!("{a:1b}.\"quoted name\"{b:2}[][{c:3}][-1]".parse::<NbtPath>().is_ok());
+        assert!("".parse::<NbtPath>().is_err());
+        assert!("a.".parse::<NbtPath>().is_err());
+        assert!("a[x]".parse::<NbtPath>().is_err());
+        assert!("a[0".parse::<NbtPath>().is_err());
+        assert_eq!("Data.Player".parse::<NbtPath>().unwrap().to_string(), "Data.Player");
+
+        let built = NbtPath::root().child("Inventory").filtered(vec![NbtTag::Byte("Slot".to_string(), 0)].into())
+            .child("minecraft:custom name").index(-1);
+        assert_eq!(built.to_string(), r#"Inventory[{Slot:0b}]."minecraft:custom name"[-1]"#);
+        assert_eq!(built.to_string().parse::<NbtPath>().unwrap(), built);
+    }
+
+    #[test]
+    fn get_and_count() {
+        let tag = player();
+        let path: NbtPath = r#"Inventory[{Slot:0b}].components."minecraft:custom_name""#.parse().unwrap();
+        assert_eq!(path.get(&tag).unwrap(), vec![NbtValue::String("Rock")]);
+        let y: NbtPath = "Pos[1]".parse().unwrap();
+        assert_eq!(y.get(&tag).unwrap()[0].as_f64(), Some(64.0));
+        let z: NbtPath = "Pos[-1]".parse().unwrap();
+        assert_eq!(z.get(&tag).unwrap()[0].as_f64(), Some(-3.5));
+        assert_eq!("Inventory[]".parse::<NbtPath>().unwrap().count(&tag), 3);
+        assert_eq!("Inventory[{id:\"minecraft:stone\"}].count".parse::<NbtPath>().unwrap().count(&tag), 2);
+        assert_eq!("{Pos:[64.0d]}.Pos".parse::<NbtPath>().unwrap().count(&tag), 1);
+        assert_eq!("{Pos:[65.0d]}.Pos".parse::<NbtPath>().unwrap().count(&tag), 0);
+        assert!(matches!("Pos[3]".parse::<NbtPath>().unwrap().get(&tag), Err(NoSuchValue(_))));
+    }
+
+    #[test]
+    fn set_and_remove() {
+        let mut tag = player();
+        let count: NbtPath = "Inventory[{id:\"minecraft:stone\"}].count".parse().unwrap();
+        assert_eq!(count.set(&mut tag, NbtTag::Int("".to_string(), 64)).unwrap(), 2);
+        assert_eq!(tag["Inventory"].try_index(2).and_then(|item| item.get("count")).and_then(NbtTag::as_i32), Some(64));
+
+        let created: NbtPath = "Data.Player.Name".parse().unwrap();
+        created.set(&mut tag, NbtTag::String("".to_string(), "Steve".to_string())).unwrap();
+        assert_eq!(tag.get_ref("Data").unwrap().get_ref("Player").unwrap().get_string("Name").unwrap(), "Steve");
+
+        let y: NbtPath = "Pos[1]".parse().unwrap();
+        assert!(matches!(y.set(&mut tag, NbtTag::Int("".to_string(), 1)), Err(WrongType(_, _))));
+        y.set(&mut tag, NbtTag::Double("".to_string(), 70.0)).unwrap();
+        assert_eq!(y.get(&tag).unwrap()[0].as_f64(), Some(70.0));
+
+        let stone: NbtPath = "Inventory[{id:\"minecraft:stone\"}]".parse().unwrap();
+        assert_eq!(stone.remove(&mut tag), 2);
+        let first: NbtPath = "Pos[0]".parse().unwrap();
+        first.insert(&mut tag, NbtTag::Double("".to_string(), 0.5)).unwrap();
+        "Pos[-1]".parse::<NbtPath>().unwrap().insert(&mut tag, NbtTag::Double("".to_string(), 9.0)).unwrap();
+        assert_eq!(Vec::<f64>::try_from(tag["Pos"].as_list().unwrap()).unwrap(), vec![0.5, 1.0, 70.0, -3.5, 9.0]);
+        assert!(matches!("Pos[9]".parse::<NbtPath>().unwrap().insert(&mut tag, NbtTag::Double("".to_string(), 0.0)), Err(NoSuchValue(_))));
+        assert_eq!("Inventory[]".parse::<NbtPath>().unwrap().count(&tag), 1);
+        assert_eq!("Pos[0]".parse::<NbtPath>().unwrap().remove(&mut tag), 1);
+        assert_eq!("Data.Player".parse::<NbtPath>().unwrap().remove(&mut tag), 1);
+        assert_eq!("Data.Player".parse::<NbtPath>().unwrap().remove(&mut tag), 0);
+    }
+
+    #[test]
+    fn failed_edits_keep_tree() {
+        let mut tag = player();
+        let original = tag.clone();
+        let name: NbtPath = "Data.Name".parse().unwrap();
+        assert!(matches!(name.set(&mut tag, NbtTag::End), Err(InvalidValue(_))));
+        assert!(matches!("a.b[0]".parse::<NbtPath>().unwrap().set(&mut tag, NbtTag::Int("".to_string(), 1)), Err(NoSuchValue(_))));
+        assert!(matches!("Pos[]".parse::<NbtPath>().unwrap().insert(&mut tag, NbtTag::Int("".to_string(), 1)), Err(WrongType(_, _))));
+        assert!(matches!("Data.Pos[0]".parse::<NbtPath>().unwrap().insert(&mut tag, NbtTag::End), Err(InvalidValue(_))));
+        assert_eq!(tag, original);
+
+        "a.b[]".parse::<NbtPath>().unwrap().insert(&mut tag, NbtTag::Int("".to_string(), 1)).unwrap();
+        assert_eq!(Vec::<i32>::try_from(tag.get_ref("a").unwrap()["b"].as_list().unwrap()).unwrap(), vec![1]);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::NbtParseError;
+use crate::NbtParseError::{WrongLength, WrongType};
+use crate::item::InventoryItem;
+use crate::level::GameType;
+use crate::model::{compound, field, key_order, optional, ordered_compound, unknown_fields};
+use crate::list::NbtList;
+use crate::types::NbtTag;
+
+/// Typed model of a player, as stored in `playerdata/<uuid>.dat` or the `Player` compound of
+/// level.dat. Keys without a typed field are kept in `other`, and the original key order is
+/// restored when converting back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerData {
+    pub data_version: Option<i32>,
+    pub pos: [f64; 3],
+    pub motion: [f64; 3],
+    /// Yaw and pitch, in degrees.
+    pub rotation: [f32; 2],
+    pub health: f32,
+    pub food_level: i32,
+    pub food_saturation_level: f32,
+    pub food_exhaustion_level: f32,
+    pub xp_level: i32,
+    /// Progress towards the next level, from 0 to 1.
+    pub xp_progress: f32,
+    pub xp_total: i32,
+    pub game_type: GameType,
+    /// Namespaced dimension id. Numeric dimensions from before 1.16 are converted on read,
+    /// and written as numbers again while they name one of the three old dimensions.
+    pub dimension: String,
+    pub selected_item_slot: Option<i32>,
+    pub inventory: Vec<InventoryItem>,
+    pub ender_items: Option<Vec<InventoryItem>>,
+    pub other: Vec<NbtTag>,
+    key_order: Vec<String>,
+    /// The numeric dimension that was read, if any.
+    legacy_dimension: Option<i32>,
+}
+
+impl PlayerData {
+    const KNOWN: [&'static str; 16] = [
+        "DataVersion", "Pos", "Motion", "Rotation", "Health", "foodLevel", "foodSaturationLevel",
+        "foodExhaustionLevel", "XpLevel", "XpP", "XpTotal", "playerGameType", "Dimension",
+        "SelectedItemSlot", "Inventory", "EnderItems",
+    ];
+
+    pub fn from_tag(tag: &NbtTag) -> Result<PlayerData, NbtParseError> {
+        let tags = compound(tag)?;
+        let (dimension, legacy_dimension) = match tag.get_ref("Dimension")? {
+            NbtTag::String(_, dimension) => (dimension.clone(), None),
+            NbtTag::Int(_, id) => (dimension_name(*id).to_string(), Some(*id)),
+            tag => return Err(WrongType("string".to_string(), tag.type_name())),
+        };
+        Ok(PlayerData {
+            data_version: optional(tag.get_int("DataVersion"))?,
+            pos: get_doubles(tag, "Pos")?,
+            motion: get_doubles(tag, "Motion")?,
+            rotation: get_floats(tag, "Rotation")?,
+            health: tag.get_float("Health")?,
+            food_level: tag.get_int("foodLevel")?,
+            food_saturation_level: tag.get_float("foodSaturationLevel")?,
+            food_exhaustion_level: tag.get_float("foodExhaustionLevel")?,
+            xp_level: tag.get_int("XpLevel")?,
+            xp_progress: tag.get_float("XpP")?,
+            xp_total: tag.get_int("XpTotal")?,
+            game_type: tag.get_int("playerGameType")?.try_into()?,
+            dimension,
+            selected_item_slot: optional(tag.get_int("SelectedItemSlot"))?,
+            inventory: InventoryItem::from_list(field(tags, "Inventory", "list", NbtTag::as_list)?)?,
+            ender_items: optional(field(tags, "EnderItems", "list", NbtTag::as_list))?.map(InventoryItem::from_list).transpose()?,
+            other: unknown_fields(tags, &Self::KNOWN),
+            key_order: key_order(tags),
+            legacy_dimension,
+        })
+    }
+
+    pub fn to_tag<S: Into<String>>(&self, name: S) -> NbtTag {
+        let mut tags = vec![
+            NbtTag::List("Pos".to_string(), NbtList::from(self.pos.to_vec())),
+            NbtTag::List("Motion".to_string(), NbtList::from(self.motion.to_vec())),
+            NbtTag::List("Rotation".to_string(), NbtList::from(self.rotation.to_vec())),
+            NbtTag::Float("Health".to_string(), self.health),
+            NbtTag::Int("foodLevel".to_string(), self.food_level),
+            NbtTag::Float("foodSaturationLevel".to_string(), self.food_saturation_level),
+            NbtTag::Float("foodExhaustionLevel".to_string(), self.food_exhaustion_level),
+            NbtTag::Int("XpLevel".to_string(), self.xp_level),
+            NbtTag::Float("XpP".to_string(), self.xp_progress),
+            NbtTag::Int("XpTotal".to_string(), self.xp_total),
+            NbtTag::Int("playerGameType".to_string(), self.game_type.id()),
+            self.dimension_tag(),
+            InventoryItem::to_list("Inventory", &self.inventory),
+        ];
+        if let Some(data_version) = self.data_version {
+            tags.push(NbtTag::Int("DataVersion".to_string(), data_version));
+        }
+        if let Some(selected_item_slot) = self.selected_item_slot {
+            tags.push(NbtTag::Int("SelectedItemSlot".to_string(), selected_item_slot));
+        }
+        if let Some(ender_items) = &self.ender_items {
+            tags.push(InventoryItem::to_list("EnderItems", ender_items));
+        }
+        tags.extend(self.other.iter().cloned());
+        ordered_compound(&name.into(), &self.key_order, tags)
+    }
+
+    /// Writes a numeric dimension that was read as a number again, unless it was changed to
+    /// a dimension without a number.
+    fn dimension_tag(&self) -> NbtTag {
+        let legacy = match self.legacy_dimension {
+            Some(id) if dimension_name(id) == self.dimension => Some(id),
+            Some(_) => [0, -1, 1].into_iter().find(|id| dimension_name(*id) == self.dimension),
+            None => None,
+        };
+        match legacy {
+            Some(id) => NbtTag::Int("Dimension".to_string(), id),
+            None => NbtTag::String("Dimension".to_string(), self.dimension.clone()),
+        }
+    }
+
+    /// Finds the item in the given inventory slot.
+    pub fn inventory_slot(&self, slot: i8) -> Option<&InventoryItem> {
+        self.inventory.iter().find(|item| item.slot == slot)
+    }
+}
+
+/// The namespaced id of a numeric dimension from before 1.16. Unknown numbers were loaded
+/// as the overworld.
+fn dimension_name(id: i32) -> &'static str {
+    match id {
+        -1 => "minecraft:the_nether",
+        1 => "minecraft:the_end",
+        _ => "minecraft:overworld",
+    }
+}
+
+fn get_doubles<const N: usize>(tag: &NbtTag, name: &str) -> Result<[f64; N], NbtParseError> {
+    let values = Vec::<f64>::try_from(field(compound(tag)?, name, "list", NbtTag::as_list)?)?;
+    let len = values.len();
+    values.try_into().map_err(|_| WrongLength(N, len))
+}
+
+fn get_floats<const N: usize>(tag: &NbtTag, name: &str) -> Result<[f32; N], NbtParseError> {
+    let values = Vec::<f32>::try_from(field(compound(tag)?, name, "list", NbtTag::as_list)?)?;
+    let len = values.len();
+    values.try_into().map_err(|_| WrongLength(N, len))
+}
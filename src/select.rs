@@ -0,0 +1,229 @@
+use crate::NbtParseError;
+use crate::compound::NbtCompound;
+use crate::list::NbtList;
+use crate::path::NbtPath;
+use crate::types::NbtTag;
+use crate::visitor::{nbt_visitor, NbtArray, NbtPrimitive, Visit, Visitor};
+
+/// A step from a list or compound to one of its children, used by [`nbt_select`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathStep {
+    Name(String),
+    Index(usize),
+}
+
+/// What selective parsing does with a tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Selection {
+    /// Parse the tag with everything in it.
+    Keep,
+    /// Parse the list or compound, but ask again for each of its children. Acts like `Skip`
+    /// for other tags.
+    Descend,
+    /// Skip the tag without parsing it.
+    Skip,
+}
+
+/// Parsing of only some parts of binary NBT. Everything else is skipped at byte level,
+/// without allocating, so scanning large files for a few values is cheap.
+pub mod nbt_select {
+    use super::*;
+
+    /// Parses the parts of uncompressed binary NBT selected by `paths`, with everything below
+    /// them, and the lists and compounds leading to them. See [`parse_selected`].
+    ///
+    /// Filters and indices in the paths select every candidate, so the result may contain
+    /// more than the paths point to, but list elements keep their indices. Using
+    /// [`NbtPath::get`] on the result gives the exact values.
+    pub fn parse_paths(data: &[u8], paths: &[NbtPath]) -> Result<NbtTag, NbtParseError> {
+        parse_selected(data, |steps| {
+            let selections = paths.iter().map(|path| path.selection(steps));
+            selections.min_by_key(|selection| *selection as u8).unwrap_or(Selection::Skip)
+        })
+    }
+
+    /// Parses uncompressed binary NBT, asking `select` for each tag what to do with it. The
+    /// steps lead from the root to the tag, and the root itself is always parsed. Lists and
+    /// compounds stay in the result when all of their children are skipped, so the partial
+    /// tree has the same structure as the full tree.
+    pub fn parse_selected<F>(data: &[u8], select: F) -> Result<NbtTag, NbtParseError>
+    where
+        F: FnMut(&[PathStep]) -> Selection,
+    {
+        let mut builder = Builder { select, steps: vec![], names: vec![], stack: vec![], result: None };
+        nbt_visitor::visit_binary(data, &mut builder)?;
+        Ok(builder.result.unwrap_or(NbtTag::End))
+    }
+}
+
+/// A list or compound being built, with whether all of its children are kept and the index
+/// of its next element.
+struct Frame {
+    tag: NbtTag,
+    keep: bool,
+    next_index: usize,
+}
+
+struct Builder<F> {
+    select: F,
+    steps: Vec<PathStep>,
+    /// Buffers of name steps that were left, reused for the next names.
+    names: Vec<String>,
+    stack: Vec<Frame>,
+    result: Option<NbtTag>,
+}
+
+impl<F: FnMut(&[PathStep]) -> Selection> Builder<F> {
+    /// Decides what to do with the next tag, leaving its step on the stack of steps.
+    fn enter(&mut self, name: &str) -> Selection {
+        let Some(parent) = self.stack.last_mut() else {
+            return Selection::Descend;
+        };
+        let step = match parent.tag {
+            NbtTag::List(_, _) => {
+                parent.next_index += 1;
+                PathStep::Index(parent.next_index - 1)
+            }
+            _ => {
+                let mut buffer = self.names.pop().unwrap_or_default();
+                buffer.clear();
+                buffer.push_str(name);
+                PathStep::Name(buffer)
+            }
+        };
+        let keep = parent.keep;
+        self.steps.push(step);
+        if keep { Selection::Keep } else { (self.select)(&self.steps) }
+    }
+
+    fn leave(&mut self) {
+        if self.stack.is_empty() {
+            return;
+        }
+        if let Some(PathStep::Name(name)) = self.steps.pop() {
+            self.names.push(name);
+        }
+    }
+
+    fn add(&mut self, tag: NbtTag) {
+        match self.stack.last_mut().map(|frame| &mut frame.tag) {
+            Some(NbtTag::List(_, list)) => {
+                list.push(tag).expect("elements of a list have its type");
+            }
+            Some(NbtTag::Compound(_, compound)) => {
+                compound.insert(tag).expect("the scan doesn't report End tags");
+            }
+            _ => self.result = Some(tag),
+        }
+    }
+
+    fn leaf(&mut self, name: &str, tag: impl FnOnce(&str) -> NbtTag) -> Visit {
+        let root = self.stack.is_empty();
+        let selection = self.enter(name);
+        self.leave();
+        if selection == Selection::Keep || root {
+            self.add(tag(name));
+        }
+        Visit::Continue
+    }
+
+    fn start(&mut self, name: &str, tag: NbtTag) -> Visit {
+        let selection = self.enter(name);
+        if selection == Selection::Skip {
+            self.leave();
+            return Visit::Skip;
+        }
+        self.stack.push(Frame { tag, keep: selection == Selection::Keep, next_index: 0 });
+        Visit::Continue
+    }
+
+    fn end(&mut self) -> Visit {
+        let frame = self.stack.pop().expect("end events follow start events");
+        self.leave();
+        self.add(frame.tag);
+        Visit::Continue
+    }
+}
+
+impl<F: FnMut(&[PathStep]) -> Selection> Visitor for Builder<F> {
+    fn start_compound(&mut self, name: &str) -> Visit {
+        self.start(name, NbtTag::Compound(name.to_string(), NbtCompound::new()))
+    }
+
+    fn end_compound(&mut self) -> Visit {
+        self.end()
+    }
+
+    fn start_list(&mut self, name: &str, element_type: i8, _len: usize) -> Visit {
+        self.start(name, NbtTag::List(name.to_string(), NbtList::new(element_type)))
+    }
+
+    fn end_list(&mut self) -> Visit {
+        self.end()
+    }
+
+    fn primitive(&mut self, name: &str, value: NbtPrimitive<'_>) -> Visit {
+        self.leaf(name, |name| value.to_tag(name))
+    }
+
+    fn array(&mut self, name: &str, array: NbtArray<'_>) -> Visit {
+        self.leaf(name, |name| array.to_tag(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::nbt_select::*;
+    use crate::nbt_writer::write_binary;
+    use crate::snbt_parser::parse_snbt;
+
+    fn paths(paths: &[&str]) -> Vec<NbtPath> {
+        paths.iter().map(|path| path.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn select_paths() {
+        let chunk = parse_snbt(r#"{Status:"minecraft:full",xPos:1,zPos:-2,InhabitedTime:40L,
+            sections:[{Y:0b,block_states:{data:[L;1L,2L]}},{Y:1b,block_states:{data:[L;3L]}}],
+            Heightmaps:{WORLD_SURFACE:[L;7L]}}"#).unwrap();
+        let data = write_binary(&chunk).unwrap();
+
+        let partial = parse_paths(&data, &paths(&["Status", "xPos", "zPos", "InhabitedTime"])).unwrap();
+        assert_eq!(partial, parse_snbt(r#"{Status:"minecraft:full",xPos:1,zPos:-2,InhabitedTime:40L}"#).unwrap());
+
+        let partial = parse_paths(&data, &paths(&["sections[].Y", "Heightmaps"])).unwrap();
+        assert_eq!(partial, parse_snbt(r#"{sections:[{Y:0b},{Y:1b}],Heightmaps:{WORLD_SURFACE:[L;7L]}}"#).unwrap());
+
+        let path: NbtPath = "sections[1].block_states".parse().unwrap();
+        let partial = parse_paths(&data, &[path.clone(), "Missing.x".parse().unwrap()]).unwrap();
+        assert_eq!(path.get(&partial).unwrap(), path.get(&chunk).unwrap());
+        assert_eq!(partial, parse_snbt(r#"{sections:[{block_states:{data:[L;1L,2L]}},{block_states:{data:[L;3L]}}]}"#).unwrap());
+
+        let path: NbtPath = "sections[{Y:1b}].block_states.data".parse().unwrap();
+        let partial = parse_paths(&data, std::slice::from_ref(&path)).unwrap();
+        assert_eq!(path.get(&partial).unwrap(), path.get(&chunk).unwrap());
+        assert_eq!(partial, parse_snbt(r#"{sections:[{Y:0b,block_states:{data:[L;1L,2L]}},{Y:1b,block_states:{data:[L;3L]}}]}"#).unwrap());
+
+        assert_eq!(parse_paths(&data, &paths(&[r#"{Status:"minecraft:full"}"#])).unwrap(), chunk);
+    }
+
+    #[test]
+    fn select_with_predicate() {
+        let tag = parse_snbt(r#"{a:{big:[I;1,2,3],small:1},b:[[1],[2,3]]}"#).unwrap();
+        let partial = parse_selected(&write_binary(&tag).unwrap(), |steps| match steps {
+            [.., PathStep::Name(name)] if name == "big" => Selection::Skip,
+            [PathStep::Name(_), PathStep::Index(1)] => Selection::Skip,
+            [_, PathStep::Index(0), _] => Selection::Keep,
+            _ => Selection::Descend,
+        }).unwrap();
+        assert_eq!(partial, parse_snbt(r#"{a:{},b:[[1]]}"#).unwrap());
+
+        let partial = parse_selected(&write_binary(&tag).unwrap(), |steps| match steps {
+            [PathStep::Name(name)] if name == "a" => Selection::Descend,
+            [_, PathStep::Name(name)] if name == "small" => Selection::Keep,
+            _ => Selection::Skip,
+        }).unwrap();
+        assert_eq!(partial, parse_snbt(r#"{a:{small:1}}"#).unwrap());
+    }
+}
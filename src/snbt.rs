@@ -0,0 +1,432 @@
+use crate::NbtParseError;
+use crate::NbtParseError::Syntax;
+use crate::compound::NbtCompound;
+use crate::list::NbtList;
+use crate::types::NbtTag;
+use crate::value::NbtValue;
+
+pub mod snbt_parser {
+    use super::*;
+
+    /// Parses a single SNBT value, the text format used in commands, e.g.
+    /// `{Slot:0b,id:"minecraft:stone",Pos:[1.0d,2.0d,3.0d]}`. The returned tag has an empty name.
+    pub fn parse_snbt(input: &str) -> Result<NbtTag, NbtParseError> {
+        let mut reader = SnbtReader::new(input);
+        let tag = reader.read_value(String::new())?;
+        reader.skip_whitespace();
+        if reader.peek().is_some() {
+            return Err(reader.error("unexpected trailing data"));
+        }
+        Ok(tag)
+    }
+}
+
+pub mod snbt_writer {
+    use super::*;
+
+    /// Formats the payload of a tag as compact SNBT, the inverse of `snbt_parser::parse_snbt`.
+    /// The name of the tag itself is left out.
+    pub fn to_snbt(tag: &NbtTag) -> String {
+        let mut snbt = String::new();
+        write_tag(&mut snbt, tag, None);
+        snbt
+    }
+
+    /// Formats the payload of a tag as indented SNBT, with every compound child and every
+    /// element of a list of compounds or lists on its own line.
+    pub fn to_snbt_pretty(tag: &NbtTag) -> String {
+        let mut snbt = String::new();
+        write_tag(&mut snbt, tag, Some(0));
+        snbt
+    }
+
+    /// Writes the value of a tag, nothing for End.
+    fn write_tag(snbt: &mut String, tag: &NbtTag, indent: Option<usize>) {
+        if let Some(value) = tag.as_value() {
+            write_value(snbt, value, indent);
+        }
+    }
+
+    /// Writes a value, compact without `indent` and else indented by `indent` levels.
+    fn write_value(snbt: &mut String, value: NbtValue<'_>, indent: Option<usize>) {
+        match value {
+            NbtValue::Byte(value) => snbt.push_str(&format!("{value}b")),
+            NbtValue::Short(value) => snbt.push_str(&format!("{value}s")),
+            NbtValue::Int(value) => snbt.push_str(&value.to_string()),
+            NbtValue::Long(value) => snbt.push_str(&format!("{value}L")),
+            NbtValue::Float(value) => snbt.push_str(&float(value, 'f')),
+            NbtValue::Double(value) => snbt.push_str(&float(value, 'd')),
+            NbtValue::ByteArray(values) => write_array(snbt, "B;", values.iter().map(|n| format!("{n}b"))),
+            NbtValue::String(value) => snbt.push_str(&quote(value)),
+            NbtValue::List(values) => {
+                let nested = matches!(values.element_type(), 9 | 10);
+                let indent = indent.filter(|_| nested && !values.is_empty());
+                write_entries(snbt, ('[', ']'), values.iter(), indent, write_value);
+            }
+            NbtValue::Compound(tags) => {
+                let indent = indent.filter(|_| !tags.is_empty());
+                write_entries(snbt, ('{', '}'), tags.iter(), indent, |snbt, tag, indent| {
+                    snbt.push_str(&key(tag.name()));
+                    snbt.push_str(if indent.is_some() { ": " } else { ":" });
+                    write_tag(snbt, tag, indent);
+                });
+            }
+            NbtValue::IntArray(values) => write_array(snbt, "I;", values.iter().map(|n| n.to_string())),
+            NbtValue::LongArray(values) => write_array(snbt, "L;", values.iter().map(|n| format!("{n}L"))),
+        }
+    }
+
+    /// Writes the entries of a list or compound between brackets, one per line if indented.
+    fn write_entries<T, I, F>(snbt: &mut String, brackets: (char, char), entries: I, indent: Option<usize>, write: F)
+    where
+        I: Iterator<Item = T>,
+        F: Fn(&mut String, T, Option<usize>),
+    {
+        snbt.push(brackets.0);
+        for (index, entry) in entries.enumerate() {
+            if index > 0 {
+                snbt.push(',');
+            }
+            if let Some(indent) = indent {
+                snbt.push('\n');
+                snbt.push_str(&"    ".repeat(indent + 1));
+            }
+            write(snbt, entry, indent.map(|indent| indent + 1));
+        }
+        if let Some(indent) = indent {
+            snbt.push('\n');
+            snbt.push_str(&"    ".repeat(indent));
+        }
+        snbt.push(brackets.1);
+    }
+
+    /// Formats a float with its suffix. Infinities are written as literals too large for the
+    /// type, which read back as infinity, and NaN as `NaNf` or `NaNd`.
+    fn float<T: Into<f64> + Copy + std::fmt::Debug>(value: T, suffix: char) -> String {
+        let wide = value.into();
+        if wide.is_nan() {
+            format!("NaN{suffix}")
+        } else if wide.is_infinite() {
+            format!("{}1e999{suffix}", if wide < 0.0 { "-" } else { "" })
+        } else {
+            format!("{value:?}{suffix}")
+        }
+    }
+
+    fn write_array<I: Iterator<Item = String>>(snbt: &mut String, prefix: &str, values: I) {
+        snbt.push('[');
+        snbt.push_str(prefix);
+        snbt.push_str(&values.collect::<Vec<_>>().join(","));
+        snbt.push(']');
+    }
+
+    /// Compound keys are only quoted when they contain characters that need it.
+    pub(crate) fn key(name: &str) -> String {
+        if !name.is_empty() && name.chars().all(is_unquoted_char) {
+            name.to_string()
+        } else {
+            quote(name)
+        }
+    }
+
+    /// Quotes a string with double quotes, or single quotes if that avoids escaping.
+    pub(crate) fn quote(string: &str) -> String {
+        let quote = if string.contains('"') && !string.contains('\'') { '\'' } else { '"' };
+        let mut quoted = String::with_capacity(string.len() + 2);
+        quoted.push(quote);
+        for c in string.chars() {
+            if c == quote || c == '\\' {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push(quote);
+        quoted
+    }
+}
+
+/// Reads SNBT from a string, keeping track of the position for error messages. Also used
+/// for the parts of other syntaxes that embed SNBT, like the filters of NBT paths.
+pub(crate) struct SnbtReader<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> SnbtReader<'a> {
+    pub(crate) fn new(input: &'a str) -> SnbtReader<'a> {
+        SnbtReader { input, pos: 0 }
+    }
+
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    pub(crate) fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    pub(crate) fn error(&self, message: &str) -> NbtParseError {
+        Syntax(message.to_string(), self.pos)
+    }
+
+    pub(crate) fn expect(&mut self, expected: char) -> Result<(), NbtParseError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.next();
+                Ok(())
+            }
+            _ => Err(self.error(&format!("expected '{expected}'"))),
+        }
+    }
+
+    pub(crate) fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next();
+        }
+    }
+
+    /// Reads the characters for which `allowed` returns true.
+    pub(crate) fn read_while<F: Fn(char) -> bool>(&mut self, allowed: F) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&allowed) {
+            self.next();
+        }
+        &self.input[start..self.pos]
+    }
+
+    /// Reads a string in single or double quotes. Backslashes escape quotes and backslashes.
+    pub(crate) fn read_quoted(&mut self) -> Result<String, NbtParseError> {
+        let quote = self.next().filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| self.error("expected a quote"))?;
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('\\') => match self.next() {
+                    Some(c) if c == '\\' || c == quote => string.push(c),
+                    _ => return Err(self.error("invalid escape sequence")),
+                },
+                Some(c) if c == quote => return Ok(string),
+                Some(c) => string.push(c),
+                None => return Err(self.error("unclosed quoted string")),
+            }
+        }
+    }
+
+    fn read_string(&mut self) -> Result<String, NbtParseError> {
+        match self.peek() {
+            Some('"' | '\'') => self.read_quoted(),
+            _ => Ok(self.read_while(is_unquoted_char).to_string()),
+        }
+    }
+
+    pub(crate) fn read_value(&mut self, name: String) -> Result<NbtTag, NbtParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => Ok(NbtTag::Compound(name, self.read_compound()?)),
+            Some('[') => self.read_list_or_array(name),
+            Some('"' | '\'') => Ok(NbtTag::String(name, self.read_quoted()?)),
+            _ => {
+                let token = self.read_while(is_unquoted_char);
+                if token.is_empty() {
+                    return Err(self.error("expected a value"));
+                }
+                let mut tag = parse_primitive(token);
+                tag.set_name(name);
+                Ok(tag)
+            }
+        }
+    }
+
+    pub(crate) fn read_compound(&mut self) -> Result<NbtCompound, NbtParseError> {
+        self.expect('{')?;
+        let mut compound = NbtCompound::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(compound);
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.read_string()?;
+            if key.is_empty() {
+                return Err(self.error("expected a key"));
+            }
+            self.skip_whitespace();
+            self.expect(':')?;
+            compound.push_unique(self.read_value(key)?)?;
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(compound),
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn read_list_or_array(&mut self, name: String) -> Result<NbtTag, NbtParseError> {
+        self.expect('[')?;
+        let rest = &self.input[self.pos..];
+        let array_type = ['B', 'I', 'L'].into_iter().find(|c| rest.starts_with(*c) && rest[1..].starts_with(';'));
+        if let Some(array_type) = array_type {
+            self.pos += 2;
+            let elements = self.read_elements()?;
+            let start = self.pos;
+            let wrong_type = |tag: &NbtTag| Syntax(format!("can't insert {} into {array_type} array", tag.type_name()), start);
+            return match array_type {
+                'B' => elements.iter().map(|tag| tag.as_i8().ok_or_else(|| wrong_type(tag))).collect::<Result<_, _>>()
+                    .map(|values| NbtTag::ByteArray(name, values)),
+                'I' => elements.iter().map(|tag| tag.as_i32().ok_or_else(|| wrong_type(tag))).collect::<Result<_, _>>()
+                    .map(|values| NbtTag::IntArray(name, values)),
+                _ => elements.iter().map(|tag| tag.as_i64().ok_or_else(|| wrong_type(tag))).collect::<Result<_, _>>()
+                    .map(|values| NbtTag::LongArray(name, values)),
+            };
+        }
+        let elements = self.read_elements()?;
+        let list = NbtList::try_from(elements).map_err(|_| self.error("list elements must have the same type"))?;
+        Ok(NbtTag::List(name, list))
+    }
+
+    /// Reads the comma separated elements of a list or array, up to and including the `]`.
+    fn read_elements(&mut self) -> Result<Vec<NbtTag>, NbtParseError> {
+        let mut elements = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(elements);
+        }
+        loop {
+            elements.push(self.read_value(String::new())?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(elements),
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+}
+
+fn is_unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+/// Interprets an unquoted token. Numbers take their type from the suffix, integers without
+/// a suffix are ints and decimals are doubles. `NaNf` and `NaNd` are the NaN floats written by
+/// `to_snbt`. `true` and `false` are bytes, and anything
+/// else that isn't a number, including out of range integers, is a string.
+fn parse_primitive(token: &str) -> NbtTag {
+    let name = String::new();
+    match token {
+        "true" => return NbtTag::Byte(name, 1),
+        "false" => return NbtTag::Byte(name, 0),
+        _ => {}
+    }
+    let (body, suffix) = token.split_at(token.len() - 1);
+    let tag = match suffix.to_ascii_lowercase().as_str() {
+        "f" if body == "NaN" => Some(NbtTag::Float(name, f32::NAN)),
+        "d" if body == "NaN" => Some(NbtTag::Double(name, f64::NAN)),
+        "b" if is_integer(body) => body.parse().ok().map(|n| NbtTag::Byte(name, n)),
+        "s" if is_integer(body) => body.parse().ok().map(|n| NbtTag::Short(name, n)),
+        "l" if is_integer(body) => body.parse().ok().map(|n| NbtTag::Long(name, n)),
+        "f" if is_decimal(body, false) => body.parse().ok().map(|n| NbtTag::Float(name, n)),
+        "d" if is_decimal(body, false) => body.parse().ok().map(|n| NbtTag::Double(name, n)),
+        _ if is_integer(token) => token.parse().ok().map(|n| NbtTag::Int(name, n)),
+        _ if is_decimal(token, true) => token.parse().ok().map(|n| NbtTag::Double(name, n)),
+        _ => None,
+    };
+    tag.unwrap_or_else(|| NbtTag::String(String::new(), token.to_string()))
+}
+
+fn is_integer(s: &str) -> bool {
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Matches decimals like `1.5`, `.5`, `1.` and `1.5e-3`. Without `require_dot`, integers
+/// like `1` and `1e3` match as well.
+fn is_decimal(s: &str, require_dot: bool) -> bool {
+    let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(index) => (&s[..index], Some(&s[index + 1..])),
+        None => (s, None),
+    };
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None),
+    };
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let digits = int.len() + frac.map_or(0, |frac| frac.len());
+    (frac.is_some() || !require_dot)
+        && digits > 0
+        && all_digits(int)
+        && frac.is_none_or(all_digits)
+        && exponent.is_none_or(is_integer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::snbt_parser::parse_snbt;
+    use super::snbt_writer::{to_snbt, to_snbt_pretty};
+
+    #[test]
+    fn parse_primitives() {
+        assert_eq!(parse_snbt("1b").unwrap(), NbtTag::Byte("".to_string(), 1));
+        assert_eq!(parse_snbt("-3S").unwrap(), NbtTag::Short("".to_string(), -3));
+        assert_eq!(parse_snbt("42").unwrap(), NbtTag::Int("".to_string(), 42));
+        assert_eq!(parse_snbt("42L").unwrap(), NbtTag::Long("".to_string(), 42));
+        assert_eq!(parse_snbt("1.5f").unwrap(), NbtTag::Float("".to_string(), 1.5));
+        assert_eq!(parse_snbt("2d").unwrap(), NbtTag::Double("".to_string(), 2.0));
+        assert_eq!(parse_snbt(".5").unwrap(), NbtTag::Double("".to_string(), 0.5));
+        assert_eq!(parse_snbt("true").unwrap(), NbtTag::Byte("".to_string(), 1));
+        assert_eq!(parse_snbt("300b").unwrap(), NbtTag::String("".to_string(), "300b".to_string()));
+        assert_eq!(parse_snbt("minecraft.stone").unwrap(), NbtTag::String("".to_string(), "minecraft.stone".to_string()));
+        assert_eq!(parse_snbt(r#"'it\'s "quoted"'"#).unwrap(), NbtTag::String("".to_string(), "it's \"quoted\"".to_string()));
+    }
+
+    #[test]
+    fn parse_nested() {
+        let tag = parse_snbt(r#" { Slot: 0b, id: "minecraft:stone", Pos: [1.0d, 2.0d], "a b": [I; 1, -2], Empty: [] } "#).unwrap();
+        assert_eq!(tag, NbtTag::Compound("".to_string(), vec![
+            NbtTag::Byte("Slot".to_string(), 0),
+            NbtTag::String("id".to_string(), "minecraft:stone".to_string()),
+            NbtTag::List("Pos".to_string(), NbtList::from(vec![1.0f64, 2.0])),
+            NbtTag::IntArray("a b".to_string(), vec![1, -2]),
+            NbtTag::List("Empty".to_string(), NbtList::default()),
+        ].into()));
+        assert_eq!(parse_snbt("[B;1b,2b]").unwrap(), NbtTag::ByteArray("".to_string(), vec![1, 2]));
+        assert_eq!(parse_snbt("[L;1L]").unwrap(), NbtTag::LongArray("".to_string(), vec![1]));
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let snbt = r#"{Slot:0b,"a b":'say "hi"',Pos:[1.5d,-2.0d],f:1.0E-7f,s:3s,l:[L;1L,-2L],b:[B;],e:[],c:{}}"#;
+        let tag = parse_snbt(snbt).unwrap();
+        assert_eq!(to_snbt(&tag), snbt.replace("1.0E-7f", "1e-7f"));
+        assert_eq!(parse_snbt(&to_snbt(&tag)).unwrap(), tag);
+        assert_eq!(to_snbt(&NbtTag::String("".to_string(), "back\\slash".to_string())), r#""back\\slash""#);
+
+        let floats = NbtTag::List("".to_string(), NbtList::from(vec![f32::INFINITY, f32::NEG_INFINITY, f32::NAN]));
+        assert_eq!(to_snbt(&floats), "[1e999f,-1e999f,NaNf]");
+        let values = Vec::<f32>::try_from(parse_snbt(&to_snbt(&floats)).unwrap().as_list().unwrap()).unwrap();
+        assert_eq!((values[0], values[1], values[2].is_nan()), (f32::INFINITY, f32::NEG_INFINITY, true));
+        assert!(parse_snbt(&to_snbt(&NbtTag::Double("".to_string(), f64::NAN))).unwrap().as_f64().unwrap().is_nan());
+        assert_eq!(parse_snbt("-1e999d").unwrap(), NbtTag::Double("".to_string(), f64::NEG_INFINITY));
+
+        let tag = parse_snbt("{a:1b,l:[{x:[1,2]},{}],e:{}}").unwrap();
+        assert_eq!(to_snbt_pretty(&tag), "{\n    a: 1b,\n    l: [\n        {\n            x: [1,2]\n        },\n        {}\n    ],\n    e: {}\n}");
+        assert_eq!(parse_snbt(&to_snbt_pretty(&tag)).unwrap(), tag);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(parse_snbt("{a:1,}"), Err(Syntax(_, 5))));
+        assert!(matches!(parse_snbt("[1, 2b]"), Err(Syntax(_, _))));
+        assert!(matches!(parse_snbt("[I;1b]"), Err(Syntax(_, _))));
+        assert!(matches!(parse_snbt("{a:1} x"), Err(Syntax(_, _))));
+        assert!(matches!(parse_snbt("{a:1,a:2}"), Err(NbtParseError::DuplicateKey(_))));
+        assert!(matches!(parse_snbt("\"open"), Err(Syntax(_, _))));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::list::type_name;
+use crate::snbt_writer::{key, quote};
+use crate::types::NbtTag;
+use crate::value::NbtValue;
+
+const RESET: &str = "\x1b[0m";
+const NAME: &str = "\x1b[36m";
+const TYPE: &str = "\x1b[2m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[33m";
+
+/// Options for [`NbtTag::to_tree_with_options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeOptions {
+    /// Lists and compounds deeper than this only show their size.
+    pub max_depth: Option<usize>,
+    /// Arrays longer than this are summarized, like `[1024 longs]`.
+    pub max_array_len: usize,
+    /// Colors names, types and values with ANSI escape codes.
+    pub colors: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> TreeOptions {
+        TreeOptions { max_depth: None, max_array_len: 16, colors: false }
+    }
+}
+
+impl TreeOptions {
+    pub fn new() -> TreeOptions {
+        TreeOptions::default()
+    }
+
+    pub fn with_max_depth(mut self, depth: usize) -> TreeOptions {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    pub fn with_max_array_len(mut self, len: usize) -> TreeOptions {
+        self.max_array_len = len;
+        self
+    }
+
+    pub fn with_colors(mut self, colors: bool) -> TreeOptions {
+        self.colors = colors;
+        self
+    }
+}
+
+impl NbtTag {
+    /// Renders the tree with one tag per line, like `Pos (list of double): 3 entries`.
+    pub fn to_tree(&self) -> String {
+        self.to_tree_with_options(&TreeOptions::default())
+    }
+
+    pub fn to_tree_with_options(&self, options: &TreeOptions) -> String {
+        let mut tree = String::new();
+        let label = if self.name().is_empty() { String::new() } else { key(self.name()) };
+        Tree { out: &mut tree, options }.tag(&label, self.as_value(), 0);
+        tree
+    }
+}
+
+struct Tree<'a> {
+    out: &'a mut String,
+    options: &'a TreeOptions,
+}
+
+impl Tree<'_> {
+    fn paint(&mut self, color: &str, text: &str) {
+        if self.options.colors {
+            self.out.push_str(color);
+            self.out.push_str(text);
+            self.out.push_str(RESET);
+        } else {
+            self.out.push_str(text);
+        }
+    }
+
+    /// Renders a value, or End for `None`.
+    fn tag(&mut self, label: &str, value: Option<NbtValue<'_>>, depth: usize) {
+        self.out.push_str(&"  ".repeat(depth));
+        if !label.is_empty() {
+            self.paint(NAME, label);
+            self.out.push(' ');
+        }
+        let type_name = match value {
+            Some(NbtValue::List(values)) => format!("list of {}", type_name(values.element_type())),
+            Some(value) => value.type_name(),
+            None => type_name(0),
+        };
+        self.paint(TYPE, &format!("({type_name})"));
+        self.out.push_str(": ");
+        let Some(value) = value else {
+            self.out.push_str("end\n");
+            return;
+        };
+        match value {
+            NbtValue::Byte(value) => self.paint(NUMBER, &value.to_string()),
+            NbtValue::Short(value) => self.paint(NUMBER, &value.to_string()),
+            NbtValue::Int(value) => self.paint(NUMBER, &value.to_string()),
+            NbtValue::Long(value) => self.paint(NUMBER, &value.to_string()),
+            NbtValue::Float(value) => self.paint(NUMBER, &format!("{value:?}")),
+            NbtValue::Double(value) => self.paint(NUMBER, &format!("{value:?}")),
+            NbtValue::ByteArray(values) => self.array(values, "bytes"),
+            NbtValue::String(value) => self.paint(STRING, &quote(value)),
+            NbtValue::List(values) => {
+                self.out.push_str(&entries(values.len()));
+                self.children(values.iter().enumerate().map(|(index, value)| (format!("[{index}]"), Some(value))), depth);
+                return;
+            }
+            NbtValue::Compound(tags) => {
+                self.out.push_str(&entries(tags.len()));
+                self.children(tags.iter().map(|tag| (key(tag.name()), tag.as_value())), depth);
+                return;
+            }
+            NbtValue::IntArray(values) => self.array(values, "ints"),
+            NbtValue::LongArray(values) => self.array(values, "longs"),
+        }
+        self.out.push('\n');
+    }
+
+    fn children<'t>(&mut self, children: impl Iterator<Item = (String, Option<NbtValue<'t>>)>, depth: usize) {
+        self.out.push('\n');
+        if self.options.max_depth.is_some_and(|max| depth >= max) {
+            return;
+        }
+        for (label, value) in children {
+            self.tag(&label, value, depth + 1);
+        }
+    }
+
+    fn array<T: ToString>(&mut self, values: &[T], unit: &str) {
+        if values.len() > self.options.max_array_len {
+            self.out.push_str(&format!("[{} {unit}]", values.len()));
+        } else {
+            let values: Vec<String> = values.iter().map(T::to_string).collect();
+            self.out.push('[');
+            self.paint(NUMBER, &values.join(", "));
+            self.out.push(']');
+        }
+    }
+}
+
+fn entries(len: usize) -> String {
+    match len {
+        1 => "1 entry".to_string(),
+        len => format!("{len} entries"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snbt_parser::parse_snbt;
+
+    #[test]
+    fn render_tree() {
+        let mut tag = parse_snbt(r#"{Name:"Steve",Pos:[1.0d,2.5d],Inv:[{id:"stone"}],Data:[L;1L,2L,3L],e:{}}"#).unwrap();
+        tag.set_name("Player");
+        assert_eq!(tag.to_tree(), "\
+Player (compound): 5 entries
+  Name (string): \"Steve\"
+  Pos (list of double): 2 entries
+    [0] (double): 1.0
+    [1] (double): 2.5
+  Inv (list of compound): 1 entry
+    [0] (compound): 1 entry
+      id (string): \"stone\"
+  Data (long array): [1, 2, 3]
+  e (compound): 0 entries
+");
+        let options = TreeOptions::new().with_max_depth(1).with_max_array_len(2);
+        assert_eq!(tag.to_tree_with_options(&options).lines().collect::<Vec<_>>(), [
+            "Player (compound): 5 entries",
+            "  Name (string): \"Steve\"",
+            "  Pos (list of double): 2 entries",
+            "  Inv (list of compound): 1 entry",
+            "  Data (long array): [3 longs]",
+            "  e (compound): 0 entries",
+        ]);
+        let colored = NbtTag::Int("a".to_string(), 1).to_tree_with_options(&TreeOptions::new().with_colors(true));
+        assert_eq!(colored, "\x1b[36ma\x1b[0m \x1b[2m(int)\x1b[0m: \x1b[33m1\x1b[0m\n");
+    }
+}
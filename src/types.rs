@@ -1,5 +1,9 @@
+use std::ops::Index;
 use crate::NbtParseError;
-use crate::NbtParseError::{NoSuchValue, TriedGettingFromNonCompound, WrongType};
+use crate::compound::NbtCompound;
+use crate::list::NbtList;
+use crate::value::{NbtValue, NbtValueMut};
+use crate::NbtParseError::{IndexOutOfBounds, InvalidValue, NoSuchValue, TriedGettingFromNonCompound, TriedIndexingNonList, WrongType};
 
 /// Enum type for all existing nbt types. Excluding End, each tag contains a name as a String
 /// and it's value.
@@ -24,10 +28,10 @@ pub enum NbtTag {
     ByteArray(String, Vec<i8>),
     /// A normal string
     String(String, String),
-    /// Vector of NbtTag, all elements are the same type
-    List(String, Vec<NbtTag>),
-    /// Vector of NbtTag, all elements can be different types
-    Compound(String, Vec<NbtTag>),
+    /// Unnamed tags of a single type, which is kept even when the list is empty
+    List(String, NbtList),
+    /// Named tags of any type, in insertion order and with unique names
+    Compound(String, NbtCompound),
     /// Vector of 32-bit signed integers
     IntArray(String, Vec<i32>),
     /// Vector of 64-bit signed integers
@@ -53,128 +57,430 @@ impl NbtTag {
         }.to_string()
     }
 
-    pub fn get<S: Into<String> + Clone>(&self, name: S) -> Result<NbtTag, NbtParseError> {
+    /// The numeric id used for this type in binary NBT.
+    pub fn type_id(&self) -> i8 {
         match self {
-            NbtTag::Compound(_, tags) => {
-                for tag in tags {
-                    if tag.get_name() == name.clone().into() {
-                        return Ok(tag.clone());
-                    }
-                }
-                Err(NoSuchValue(name.into()))
-            }
+            NbtTag::End => 0,
+            NbtTag::Byte(_, _) => 1,
+            NbtTag::Short(_, _) => 2,
+            NbtTag::Int(_, _) => 3,
+            NbtTag::Long(_, _) => 4,
+            NbtTag::Float(_, _) => 5,
+            NbtTag::Double(_, _) => 6,
+            NbtTag::ByteArray(_, _) => 7,
+            NbtTag::String(_, _) => 8,
+            NbtTag::List(_, _) => 9,
+            NbtTag::Compound(_, _) => 10,
+            NbtTag::IntArray(_, _) => 11,
+            NbtTag::LongArray(_, _) => 12,
+        }
+    }
+
+    /// Returns a copy of the named child of a compound. Use [`NbtTag::get_ref`] to borrow it
+    /// instead.
+    pub fn get<S: AsRef<str>>(&self, name: S) -> Result<NbtTag, NbtParseError> {
+        self.get_ref(name.as_ref()).cloned()
+    }
+
+    /// Returns a reference to the named child of a compound, without cloning it.
+    pub fn get_ref(&self, name: &str) -> Result<&NbtTag, NbtParseError> {
+        match self {
+            NbtTag::Compound(_, tags) => tags.get(name).ok_or_else(|| NoSuchValue(name.to_string())),
             _ => Err(TriedGettingFromNonCompound(self.type_name()))
         }
     }
 
-    pub fn get_byte<S: Into<String> + Clone>(&self, name: S) -> Result<i8, NbtParseError> {
-        let tag = self.get(name)?;
+    /// Returns the named child of a compound, or `None` if there is no such child or this
+    /// isn't a compound.
+    pub fn try_get(&self, name: &str) -> Option<&NbtTag> {
+        self.as_compound().and_then(|tags| tags.get(name))
+    }
+
+    /// Like [`NbtTag::get_mut`], but returns `None` if there is no such child or this isn't a
+    /// compound.
+    pub fn try_get_mut(&mut self, name: &str) -> Option<NbtValueMut<'_>> {
+        match self {
+            NbtTag::Compound(_, tags) => tags.get_mut(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the element at `index` of a list, or `None` if it is out of bounds or this
+    /// isn't a list.
+    ///
+    /// This replaces `Index<usize>`, which `NbtTag` doesn't implement: lists store their
+    /// elements in typed vectors rather than as tags, so there is no element `NbtTag` an
+    /// index operator could return a reference to.
+    pub fn try_index(&self, index: usize) -> Option<NbtValue<'_>> {
+        self.as_list().and_then(|tags| tags.get(index))
+    }
+
+    /// Like [`NbtTag::list_get_mut`], but returns `None` if the index is out of bounds or this
+    /// isn't a list.
+    pub fn try_index_mut(&mut self, index: usize) -> Option<NbtValueMut<'_>> {
+        match self {
+            NbtTag::List(_, tags) => tags.get_mut(index),
+            _ => None,
+        }
+    }
+
+    pub fn get_byte<S: AsRef<str>>(&self, name: S) -> Result<i8, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::Byte(_, value) = tag {
-            Ok(value)
+            Ok(*value)
         } else {
             Err(WrongType("byte".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_short<S: Into<String> + Clone>(&self, name: S) -> Result<i16, NbtParseError> {
-        let tag = self.get(name)?;
+    pub fn get_short<S: AsRef<str>>(&self, name: S) -> Result<i16, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::Short(_, value) = tag {
-            Ok(value)
+            Ok(*value)
         } else {
             Err(WrongType("short".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_int<S: Into<String> + Clone>(&self, name: S) -> Result<i32, NbtParseError> {
-        let tag = self.get(name)?;
+    pub fn get_int<S: AsRef<str>>(&self, name: S) -> Result<i32, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::Int(_, value) = tag {
-            Ok(value)
+            Ok(*value)
         } else {
             Err(WrongType("int".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_long<S: Into<String> + Clone>(&self, name: S) -> Result<i64, NbtParseError> {
-        let tag = self.get(name)?;
+    pub fn get_long<S: AsRef<str>>(&self, name: S) -> Result<i64, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::Long(_, value) = tag {
-            Ok(value)
+            Ok(*value)
         } else {
             Err(WrongType("long".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_float<S: Into<String> + Clone>(&self, name: S) -> Result<f32, NbtParseError> {
-        let tag = self.get(name)?;
+    pub fn get_float<S: AsRef<str>>(&self, name: S) -> Result<f32, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::Float(_, value) = tag {
-            Ok(value)
+            Ok(*value)
         } else {
             Err(WrongType("float".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_double<S: Into<String> + Clone>(&self, name: S) -> Result<f64, NbtParseError> {
-        let tag = self.get(name)?;
+    pub fn get_double<S: AsRef<str>>(&self, name: S) -> Result<f64, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::Double(_, value) = tag {
-            Ok(value)
+            Ok(*value)
         } else {
             Err(WrongType("double".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_byte_array<S: Into<String> + Clone>(&self, name: S) -> Result<Vec<i8>, NbtParseError> {
-        let tag = self.get(name)?;
+    /// Returns a copy of the named child's value. [`NbtTag::get_ref`] with [`NbtTag::as_byte_array`]
+    /// borrows it instead.
+    pub fn get_byte_array<S: AsRef<str>>(&self, name: S) -> Result<Vec<i8>, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::ByteArray(_, value) = tag {
-            Ok(value)
+            Ok(value.clone())
         } else {
             Err(WrongType("byte array".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_string<S: Into<String> + Clone>(&self, name: S) -> Result<String, NbtParseError> {
-        let tag = self.get(name)?;
+    /// Returns a copy of the named child's value. [`NbtTag::get_ref`] with [`NbtTag::as_str`]
+    /// borrows it instead.
+    pub fn get_string<S: AsRef<str>>(&self, name: S) -> Result<String, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::String(_, value) = tag {
-            Ok(value)
+            Ok(value.clone())
         } else {
             Err(WrongType("string".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_list<S: Into<String> + Clone>(&self, name: S) -> Result<Vec<NbtTag>, NbtParseError> {
-        let tag = self.get(name)?;
+    /// Returns a copy of the named child's value. [`NbtTag::get_ref`] with [`NbtTag::as_list`]
+    /// borrows it instead.
+    pub fn get_list<S: AsRef<str>>(&self, name: S) -> Result<NbtList, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::List(_, value) = tag {
-            Ok(value)
+            Ok(value.clone())
         } else {
             Err(WrongType("list".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_compound<S: Into<String> + Clone>(&self, name: S) -> Result<Vec<NbtTag>, NbtParseError> {
-        let tag = self.get(name)?;
+    /// Returns a copy of the named child's value. [`NbtTag::get_ref`] with [`NbtTag::as_compound`]
+    /// borrows it instead.
+    pub fn get_compound<S: AsRef<str>>(&self, name: S) -> Result<NbtCompound, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::Compound(_, value) = tag {
-            Ok(value)
+            Ok(value.clone())
         } else {
             Err(WrongType("compount".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_int_array<S: Into<String> + Clone>(&self, name: S) -> Result<Vec<i32>, NbtParseError> {
-        let tag = self.get(name)?;
+    /// Returns a copy of the named child's value. [`NbtTag::get_ref`] with [`NbtTag::as_int_array`]
+    /// borrows it instead.
+    pub fn get_int_array<S: AsRef<str>>(&self, name: S) -> Result<Vec<i32>, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::IntArray(_, value) = tag {
-            Ok(value)
+            Ok(value.clone())
         } else {
             Err(WrongType("int array".to_string(), tag.type_name()))
         }
     }
 
-    pub fn get_long_array<S: Into<String> + Clone>(&self, name: S) -> Result<Vec<i64>, NbtParseError> {
-        let tag = self.get(name)?;
+    /// Returns a copy of the named child's value. [`NbtTag::get_ref`] with [`NbtTag::as_long_array`]
+    /// borrows it instead.
+    pub fn get_long_array<S: AsRef<str>>(&self, name: S) -> Result<Vec<i64>, NbtParseError> {
+        let tag = self.get_ref(name.as_ref())?;
         if let NbtTag::LongArray(_, value) = tag {
-            Ok(value)
+            Ok(value.clone())
         } else {
             Err(WrongType("long array".to_string(), tag.type_name()))
         }
     }
 
+    /// Returns mutable access to the value of the named child of a compound. The child keeps
+    /// its name and type, use [`NbtTag::rename`] and [`NbtTag::insert`] to change those.
+    pub fn get_mut<S: AsRef<str>>(&mut self, name: S) -> Result<NbtValueMut<'_>, NbtParseError> {
+        let type_name = self.type_name();
+        match self {
+            NbtTag::Compound(_, tags) => {
+                let name = name.as_ref();
+                tags.get_mut(name).ok_or_else(|| NoSuchValue(name.to_string()))
+            }
+            _ => Err(TriedGettingFromNonCompound(type_name))
+        }
+    }
+
+    /// Inserts a child into a compound. A child with the same name is replaced in place and
+    /// returned, otherwise the tag is appended.
+    pub fn insert(&mut self, tag: NbtTag) -> Result<Option<NbtTag>, NbtParseError> {
+        if tag == NbtTag::End {
+            return Err(InvalidValue("end tags can't be inserted into a compound".to_string()));
+        }
+        let type_name = self.type_name();
+        match self {
+            NbtTag::Compound(_, tags) => tags.insert(tag),
+            _ => Err(TriedGettingFromNonCompound(type_name))
+        }
+    }
+
+    /// Removes and returns the named child of a compound.
+    pub fn remove<S: AsRef<str>>(&mut self, name: S) -> Result<NbtTag, NbtParseError> {
+        let type_name = self.type_name();
+        match self {
+            NbtTag::Compound(_, tags) => {
+                let name = name.as_ref();
+                tags.remove(name).ok_or_else(|| NoSuchValue(name.to_string()))
+            }
+            _ => Err(TriedGettingFromNonCompound(type_name))
+        }
+    }
+
+    /// Renames a child of a compound, keeping its position. Another child already using the
+    /// new name is removed and returned.
+    pub fn rename<S: AsRef<str>, T: AsRef<str>>(&mut self, name: S, new_name: T) -> Result<Option<NbtTag>, NbtParseError> {
+        let type_name = self.type_name();
+        match self {
+            NbtTag::Compound(_, tags) => tags.rename(name.as_ref(), new_name.as_ref()),
+            _ => Err(TriedGettingFromNonCompound(type_name))
+        }
+    }
+
+    /// Sets the name of this tag. Does nothing for End.
+    pub fn set_name<S: Into<String>>(&mut self, new_name: S) {
+        match self {
+            NbtTag::End => {}
+            NbtTag::Byte(name, _) => *name = new_name.into(),
+            NbtTag::Short(name, _) => *name = new_name.into(),
+            NbtTag::Int(name, _) => *name = new_name.into(),
+            NbtTag::Long(name, _) => *name = new_name.into(),
+            NbtTag::Float(name, _) => *name = new_name.into(),
+            NbtTag::Double(name, _) => *name = new_name.into(),
+            NbtTag::ByteArray(name, _) => *name = new_name.into(),
+            NbtTag::String(name, _) => *name = new_name.into(),
+            NbtTag::List(name, _) => *name = new_name.into(),
+            NbtTag::Compound(name, _) => *name = new_name.into(),
+            NbtTag::IntArray(name, _) => *name = new_name.into(),
+            NbtTag::LongArray(name, _) => *name = new_name.into(),
+        }
+    }
+
+    /// Returns mutable access to the value of an element of a list. The element keeps its
+    /// type, use [`NbtTag::list_set`] to replace it.
+    pub fn list_get_mut(&mut self, index: usize) -> Result<NbtValueMut<'_>, NbtParseError> {
+        let type_name = self.type_name();
+        match self {
+            NbtTag::List(_, tags) => {
+                let len = tags.len();
+                tags.get_mut(index).ok_or(IndexOutOfBounds(index, len))
+            }
+            _ => Err(TriedIndexingNonList(type_name))
+        }
+    }
+
+    /// Replaces the element at `index` of a list and returns the old element. The new element
+    /// must have the list's element type, and its name is cleared.
+    pub fn list_set(&mut self, index: usize, tag: NbtTag) -> Result<NbtTag, NbtParseError> {
+        let type_name = self.type_name();
+        match self {
+            NbtTag::List(_, tags) => tags.set(index, tag),
+            _ => Err(TriedIndexingNonList(type_name))
+        }
+    }
+
+    /// Appends an element to a list. The element must have the list's element type, and its
+    /// name is cleared.
+    pub fn list_push(&mut self, tag: NbtTag) -> Result<(), NbtParseError> {
+        let len = self.list_len()?;
+        self.list_insert(len, tag)
+    }
+
+    /// Inserts an element into a list at `index`, shifting the following elements. The element
+    /// must have the list's element type, and its name is cleared.
+    pub fn list_insert(&mut self, index: usize, tag: NbtTag) -> Result<(), NbtParseError> {
+        let type_name = self.type_name();
+        match self {
+            NbtTag::List(_, tags) => tags.insert(index, tag),
+            _ => Err(TriedIndexingNonList(type_name))
+        }
+    }
+
+    /// Removes and returns the element at `index` of a list.
+    pub fn list_remove(&mut self, index: usize) -> Result<NbtTag, NbtParseError> {
+        let type_name = self.type_name();
+        match self {
+            NbtTag::List(_, tags) => {
+                let len = tags.len();
+                tags.remove(index).ok_or(IndexOutOfBounds(index, len))
+            }
+            _ => Err(TriedIndexingNonList(type_name))
+        }
+    }
+
+    pub fn list_len(&self) -> Result<usize, NbtParseError> {
+        match self {
+            NbtTag::List(_, tags) => Ok(tags.len()),
+            _ => Err(TriedIndexingNonList(self.type_name()))
+        }
+    }
+
+    /// The name of this tag, without allocating. End tags have an empty name.
+    pub fn name(&self) -> &str {
+        match self {
+            NbtTag::End => "",
+            NbtTag::Byte(name, _) => name,
+            NbtTag::Short(name, _) => name,
+            NbtTag::Int(name, _) => name,
+            NbtTag::Long(name, _) => name,
+            NbtTag::Float(name, _) => name,
+            NbtTag::Double(name, _) => name,
+            NbtTag::ByteArray(name, _) => name,
+            NbtTag::String(name, _) => name,
+            NbtTag::List(name, _) => name,
+            NbtTag::Compound(name, _) => name,
+            NbtTag::IntArray(name, _) => name,
+            NbtTag::LongArray(name, _) => name,
+        }
+    }
+
+    pub fn as_i8(&self) -> Option<i8> {
+        if let NbtTag::Byte(_, value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_i16(&self) -> Option<i16> {
+        if let NbtTag::Short(_, value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        if let NbtTag::Int(_, value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        if let NbtTag::Long(_, value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        if let NbtTag::Float(_, value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        if let NbtTag::Double(_, value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_byte_array(&self) -> Option<&[i8]> {
+        if let NbtTag::ByteArray(_, value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        if let NbtTag::String(_, value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_list(&self) -> Option<&NbtList> {
+        if let NbtTag::List(_, value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_compound(&self) -> Option<&NbtCompound> {
+        if let NbtTag::Compound(_, value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_int_array(&self) -> Option<&[i32]> {
+        if let NbtTag::IntArray(_, value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_long_array(&self) -> Option<&[i64]> {
+        if let NbtTag::LongArray(_, value) = self { Some(value) } else { None }
+    }
+
+    /// The value of this tag without its name, `None` for End.
+    pub fn as_value(&self) -> Option<NbtValue<'_>> {
+        Some(match self {
+            NbtTag::End => return None,
+            NbtTag::Byte(_, value) => NbtValue::Byte(*value),
+            NbtTag::Short(_, value) => NbtValue::Short(*value),
+            NbtTag::Int(_, value) => NbtValue::Int(*value),
+            NbtTag::Long(_, value) => NbtValue::Long(*value),
+            NbtTag::Float(_, value) => NbtValue::Float(*value),
+            NbtTag::Double(_, value) => NbtValue::Double(*value),
+            NbtTag::ByteArray(_, value) => NbtValue::ByteArray(value),
+            NbtTag::String(_, value) => NbtValue::String(value),
+            NbtTag::List(_, value) => NbtValue::List(value),
+            NbtTag::Compound(_, value) => NbtValue::Compound(value),
+            NbtTag::IntArray(_, value) => NbtValue::IntArray(value),
+            NbtTag::LongArray(_, value) => NbtValue::LongArray(value),
+        })
+    }
+
+    /// Mutable access to the value of this tag, which can't change its type or name. `None`
+    /// for End.
+    pub fn as_value_mut(&mut self) -> Option<NbtValueMut<'_>> {
+        self.name_and_value_mut().map(|(_, value)| value)
+    }
+
+    /// The name of this tag together with mutable access to its value.
+    pub(crate) fn name_and_value_mut(&mut self) -> Option<(&str, NbtValueMut<'_>)> {
+        Some(match self {
+            NbtTag::End => return None,
+            NbtTag::Byte(name, value) => (name, NbtValueMut::Byte(value)),
+            NbtTag::Short(name, value) => (name, NbtValueMut::Short(value)),
+            NbtTag::Int(name, value) => (name, NbtValueMut::Int(value)),
+            NbtTag::Long(name, value) => (name, NbtValueMut::Long(value)),
+            NbtTag::Float(name, value) => (name, NbtValueMut::Float(value)),
+            NbtTag::Double(name, value) => (name, NbtValueMut::Double(value)),
+            NbtTag::ByteArray(name, value) => (name, NbtValueMut::ByteArray(value)),
+            NbtTag::String(name, value) => (name, NbtValueMut::String(value)),
+            NbtTag::List(name, value) => (name, NbtValueMut::List(value)),
+            NbtTag::Compound(name, value) => (name, NbtValueMut::Compound(value)),
+            NbtTag::IntArray(name, value) => (name, NbtValueMut::IntArray(value)),
+            NbtTag::LongArray(name, value) => (name, NbtValueMut::LongArray(value)),
+        })
+    }
+
     pub fn get_name(&self) -> String {
         match self {
             NbtTag::End => "".to_string(),
@@ -192,4 +498,116 @@ impl NbtTag {
             NbtTag::LongArray(name, _) => name.clone(),
         }
     }
-}
\ No newline at end of file
+}
+
+impl Index<&str> for NbtTag {
+    type Output = NbtTag;
+
+    /// Returns the named child of a compound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this isn't a compound or has no child with the name, use
+    /// [`NbtTag::try_get`] for fields that may be missing. List elements aren't stored as
+    /// tags, so there is no `Index<usize>`; use [`NbtTag::try_index`] instead.
+    fn index(&self, name: &str) -> &Self::Output {
+        match self {
+            NbtTag::Compound(_, tags) => tags.get(name).unwrap_or_else(|| panic!("No such value: {name}")),
+            _ => panic!("Tried getting {name} from non-compound tag of type {}", self.type_name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compound() -> NbtTag {
+        NbtTag::Compound("".to_string(), vec![
+            NbtTag::Int("a".to_string(), 1),
+            NbtTag::String("b".to_string(), "b".to_string()),
+            NbtTag::List("list".to_string(), vec![NbtTag::Short("".to_string(), 1)].try_into().unwrap()),
+        ].into())
+    }
+
+    #[test]
+    fn borrow_children() {
+        let tag = compound();
+        assert_eq!(tag.get_ref("a").unwrap().as_i32(), Some(1));
+        assert_eq!(tag.get_ref("a").unwrap().as_i64(), None);
+        assert_eq!(tag.get_ref("b").unwrap().as_str(), Some("b"));
+        assert_eq!(tag.get_ref("b").unwrap().name(), "b");
+        assert_eq!(tag.get_ref("list").unwrap().as_list().unwrap().get(0), Some(NbtValue::Short(1)));
+        assert_eq!(tag.as_compound().unwrap().len(), 3);
+        assert!(tag.as_list().is_none());
+        assert!(matches!(tag.get_ref("missing"), Err(NoSuchValue(_))));
+        assert!(matches!(NbtTag::End.get_ref("a"), Err(TriedGettingFromNonCompound(_))));
+    }
+
+    #[test]
+    fn index_and_try_get() {
+        let mut tag = compound();
+        assert_eq!(tag["a"].as_i32(), Some(1));
+        assert_eq!(tag.try_get("list").and_then(|list| list.try_index(0)).and_then(|tag| tag.as_i16()), Some(1));
+        assert!(tag.try_get("missing").is_none());
+        assert!(tag["list"].try_index(1).is_none());
+        assert!(tag["a"].try_get("a").is_none());
+        tag.try_get_mut("list").and_then(|list| list.into_list()).unwrap().set(0, NbtTag::Short("".to_string(), 2)).unwrap();
+        tag.try_get_mut("a").unwrap().set(NbtTag::Int("a".to_string(), 3)).unwrap();
+        assert!(tag.try_get_mut("missing").is_none());
+        assert!(tag.try_index_mut(0).is_none());
+        assert_eq!(tag["list"].try_index(0).and_then(|value| value.as_i16()), Some(2));
+        assert_eq!(tag["a"].as_i32(), Some(3));
+        assert!(std::panic::catch_unwind(|| compound()["missing"].clone()).is_err());
+    }
+
+    #[test]
+    fn edit_compound() {
+        let mut tag = compound();
+        tag.get_mut("a").unwrap().set(NbtTag::Int("ignored".to_string(), 5)).unwrap();
+        assert!(matches!(tag.get_mut("a").unwrap().set(NbtTag::Long("a".to_string(), 5)), Err(WrongType(_, _))));
+        assert_eq!(tag.get_int("a").unwrap(), 5);
+
+        assert_eq!(tag.insert(NbtTag::Long("b".to_string(), 2)).unwrap(), Some(NbtTag::String("b".to_string(), "b".to_string())));
+        assert_eq!(tag.insert(NbtTag::Byte("c".to_string(), 3)).unwrap(), None);
+        assert_eq!(tag.get_long("b").unwrap(), 2);
+
+        assert_eq!(tag.rename("a", "c").unwrap(), Some(NbtTag::Byte("c".to_string(), 3)));
+        assert_eq!(tag.get_int("c").unwrap(), 5);
+        assert!(tag.try_get("a").is_none());
+        assert!(matches!(tag.rename("a", "d"), Err(NoSuchValue(_))));
+        tag.insert(NbtTag::Byte("a".to_string(), 1)).unwrap();
+        let names: Vec<_> = tag.as_compound().unwrap().iter().map(NbtTag::name).collect();
+        assert_eq!(names, vec!["c", "b", "list", "a"]);
+        assert_eq!(tag.remove("c").unwrap(), NbtTag::Int("c".to_string(), 5));
+        assert!(matches!(tag.remove("c"), Err(NoSuchValue(_))));
+        assert!(matches!(tag.insert(NbtTag::End), Err(InvalidValue(_))));
+        assert!(matches!(NbtTag::Int("".to_string(), 0).insert(NbtTag::End), Err(InvalidValue(_))));
+        assert!(matches!(NbtTag::Int("".to_string(), 0).remove("a"), Err(TriedGettingFromNonCompound(_))));
+    }
+
+    #[test]
+    fn edit_list() {
+        let mut list = NbtTag::List("list".to_string(), NbtList::from(vec![1i16]));
+        list.list_push(NbtTag::Short("named".to_string(), 3)).unwrap();
+        list.list_insert(1, NbtTag::Short("".to_string(), 2)).unwrap();
+        assert!(matches!(list.list_push(NbtTag::Int("".to_string(), 4)), Err(WrongType(_, _))));
+        assert!(matches!(list.list_insert(5, NbtTag::Short("".to_string(), 4)), Err(IndexOutOfBounds(5, 3))));
+        assert_eq!(list.as_list().unwrap(), &NbtList::from(vec![1i16, 2, 3]));
+
+        assert_eq!(list.list_remove(0).unwrap(), NbtTag::Short("".to_string(), 1));
+        if let NbtValueMut::Short(value) = list.list_get_mut(0).unwrap() {
+            *value = 7;
+        }
+        assert_eq!(list.list_set(1, NbtTag::Short("named".to_string(), 8)).unwrap(), NbtTag::Short("".to_string(), 3));
+        assert!(matches!(list.list_set(1, NbtTag::Int("".to_string(), 8)), Err(WrongType(_, _))));
+        assert!(matches!(list.list_set(2, NbtTag::Short("".to_string(), 8)), Err(IndexOutOfBounds(2, 2))));
+        assert_eq!(list.as_list().unwrap(), &NbtList::from(vec![7i16, 8]));
+        assert!(matches!(list.list_remove(2), Err(IndexOutOfBounds(2, 2))));
+
+        let mut tag = compound();
+        tag.get_mut("list").unwrap().into_list().unwrap().push(NbtTag::Short("".to_string(), 2)).unwrap();
+        assert_eq!(tag.get_list("list").unwrap(), NbtList::from(vec![1i16, 2]));
+        assert!(matches!(tag.list_push(NbtTag::Short("".to_string(), 1)), Err(TriedIndexingNonList(_))));
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::NbtParseError;
+use crate::NbtParseError::{InvalidValue, WrongLength, WrongType};
+use crate::model::{compound, field, optional};
+use crate::types::NbtTag;
+
+/// A 128-bit UUID, as used for entities and players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Uuid(pub u128);
+
+impl Uuid {
+    /// Reads the 1.16+ form, four ints with the most significant first.
+    pub fn from_int_array(ints: &[i32]) -> Result<Uuid, NbtParseError> {
+        if ints.len() != 4 {
+            return Err(WrongLength(4, ints.len()));
+        }
+        Ok(Uuid(ints.iter().fold(0, |uuid, n| uuid << 32 | *n as u32 as u128)))
+    }
+
+    /// Reads the pre-1.16 form, split into the most and least significant longs.
+    pub fn from_most_least(most: i64, least: i64) -> Uuid {
+        Uuid((most as u64 as u128) << 64 | least as u64 as u128)
+    }
+
+    pub fn to_int_array(&self) -> [i32; 4] {
+        [(self.0 >> 96) as i32, (self.0 >> 64) as i32, (self.0 >> 32) as i32, self.0 as i32]
+    }
+
+    pub fn most(&self) -> i64 {
+        (self.0 >> 64) as i64
+    }
+
+    pub fn least(&self) -> i64 {
+        self.0 as i64
+    }
+
+    /// Converts the UUID into an int array tag, the form used since 1.16.
+    pub fn to_tag<S: Into<String>>(&self, name: S) -> NbtTag {
+        NbtTag::IntArray(name.into(), self.to_int_array().to_vec())
+    }
+
+    /// Converts the UUID into the `<prefix>Most` and `<prefix>Least` long tags used before 1.16.
+    pub fn to_legacy_tags(&self, prefix: &str) -> [NbtTag; 2] {
+        [
+            NbtTag::Long(format!("{prefix}Most"), self.most()),
+            NbtTag::Long(format!("{prefix}Least"), self.least()),
+        ]
+    }
+}
+
+impl Display for Uuid {
+    /// Formats the UUID in its hyphenated form, e.g. `29308414-ff71-3cdd-a4fa-c0ef7b0196d4`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let hex = format!("{:032x}", self.0);
+        write!(f, "{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = NbtParseError;
+
+    /// Parses a UUID in the hyphenated 8-4-4-4-12 form or as 32 plain hex digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let groups = s.split('-').map(str::len).collect::<Vec<_>>();
+        let valid = (groups == [8, 4, 4, 4, 12] || groups == [32]) && s.chars().all(|c| c == '-' || c.is_ascii_hexdigit());
+        if !valid {
+            return Err(InvalidValue(format!("invalid UUID {s}")));
+        }
+        Ok(Uuid(u128::from_str_radix(&s.replace('-', ""), 16).expect("the UUID is 32 hex digits")))
+    }
+}
+
+impl NbtTag {
+    /// Reads a UUID stored as a 4-int array, like `UUID` on entities.
+    pub fn get_uuid<S: AsRef<str>>(&self, name: S) -> Result<Uuid, NbtParseError> {
+        Uuid::from_int_array(field(compound(self)?, name.as_ref(), "int array", NbtTag::as_int_array)?)
+    }
+
+    /// Reads a UUID stored as `<prefix>Most` and `<prefix>Least` longs, like `UUIDMost` and
+    /// `UUIDLeast` on entities before 1.16.
+    pub fn get_legacy_uuid(&self, prefix: &str) -> Result<Uuid, NbtParseError> {
+        Ok(Uuid::from_most_least(self.get_long(format!("{prefix}Most"))?, self.get_long(format!("{prefix}Least"))?))
+    }
+
+    /// Stores a UUID in a compound as a 4-int array, the form used since 1.16.
+    pub fn set_uuid<S: Into<String>>(&mut self, name: S, uuid: Uuid) -> Result<(), NbtParseError> {
+        self.insert(uuid.to_tag(name))?;
+        Ok(())
+    }
+
+    /// Stores a UUID in a compound as the `<prefix>Most` and `<prefix>Least` longs used before
+    /// 1.16.
+    pub fn set_legacy_uuid(&mut self, prefix: &str, uuid: Uuid) -> Result<(), NbtParseError> {
+        for tag in uuid.to_legacy_tags(prefix) {
+            self.insert(tag)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a UUID in any of the forms used over time: a 4-int array named `name`, the
+    /// `<name>Most` and `<name>Least` longs, or a hyphenated string named `name`.
+    pub fn get_any_uuid(&self, name: &str) -> Result<Uuid, NbtParseError> {
+        match optional(self.get_ref(name))? {
+            Some(NbtTag::IntArray(_, ints)) => Uuid::from_int_array(ints),
+            Some(NbtTag::String(_, string)) => string.parse(),
+            Some(tag) => Err(WrongType("int array".to_string(), tag.type_name())),
+            None => self.get_legacy_uuid(name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int_array_form() {
+        let uuid = Uuid::from_int_array(&[691045396, -9356067, -1527070481, 2063701716]).unwrap();
+        assert_eq!(uuid.to_string(), "29308414-ff71-3cdd-a4fa-c0ef7b0196d4");
+        assert_eq!(uuid.to_int_array(), [691045396, -9356067, -1527070481, 2063701716]);
+        assert_eq!("29308414-ff71-3cdd-a4fa-c0ef7b0196d4".parse::<Uuid>().unwrap(), uuid);
+        assert_eq!("29308414ff713cdda4fac0ef7b0196d4".parse::<Uuid>().unwrap(), uuid);
+        for invalid in ["29307f14-ff6e", "+9308414-ff71-3cdd-a4fa-c0ef7b0196d4", "+29308414ff713cdda4fac0ef7b0196d4",
+            "2930841-4ff71-3cdd-a4fa-c0ef7b0196d4", "29308414-ff713cdd-a4fa-c0ef7b0196d4-", "29308414ff71-3cdd-a4fa-c0ef-7b0196d4"] {
+            assert!(invalid.parse::<Uuid>().is_err(), "{invalid}");
+        }
+        assert!(matches!(Uuid::from_int_array(&[1, 2]), Err(WrongLength(4, 2))));
+    }
+
+    #[test]
+    fn legacy_form() {
+        let uuid: Uuid = "29308414-ff71-3cdd-a4fa-c0ef7b0196d4".parse().unwrap();
+        let mut tags = uuid.to_legacy_tags("Owner").to_vec();
+        tags.push(uuid.to_tag("UUID"));
+        let entity = NbtTag::Compound("".to_string(), tags.into());
+        assert_eq!(entity.get_legacy_uuid("Owner").unwrap(), uuid);
+        assert_eq!(entity.get_any_uuid("Owner").unwrap(), uuid);
+        assert_eq!(entity.get_uuid("UUID").unwrap(), uuid);
+        assert_eq!(entity.get_any_uuid("UUID").unwrap(), uuid);
+        assert!(entity.get_any_uuid("Leash").is_err());
+    }
+
+    #[test]
+    fn set_forms() {
+        let uuid: Uuid = "29308414-ff71-3cdd-a4fa-c0ef7b0196d4".parse().unwrap();
+        let mut entity = NbtTag::Compound("".to_string(), vec![NbtTag::IntArray("UUID".to_string(), vec![0; 4])].into());
+        entity.set_uuid("UUID", uuid).unwrap();
+        entity.set_legacy_uuid("Owner", uuid).unwrap();
+        let mut expected = vec![uuid.to_tag("UUID")];
+        expected.extend(uuid.to_legacy_tags("Owner"));
+        assert_eq!(entity, NbtTag::Compound("".to_string(), expected.into()));
+        assert!(NbtTag::Int("".to_string(), 0).set_uuid("UUID", uuid).is_err());
+    }
+}
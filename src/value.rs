@@ -0,0 +1,262 @@
+use crate::NbtParseError;
+use crate::NbtParseError::WrongType;
+use crate::compound::NbtCompound;
+use crate::list::NbtList;
+use crate::types::NbtTag;
+
+/// The payload of a tag without its name, borrowed from a tree. [`NbtTag::as_value`] gives
+/// the value of a tag and [`NbtValue::to_tag`] names a value again. End has no value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NbtValue<'a> {
+    /// 8-bit signed integer
+    Byte(i8),
+    /// 16-bit signed integer
+    Short(i16),
+    /// 32-bit signed integer
+    Int(i32),
+    /// 64-bit signed integer
+    Long(i64),
+    /// 32-bit IEEE 754-2008 float
+    Float(f32),
+    /// 64-bit IEEE 754-2008 float
+    Double(f64),
+    /// Vector of 8-bit signed integers
+    ByteArray(&'a [i8]),
+    /// A normal string
+    String(&'a str),
+    /// Values of a single type
+    List(&'a NbtList),
+    /// Named tags of any type
+    Compound(&'a NbtCompound),
+    /// Vector of 32-bit signed integers
+    IntArray(&'a [i32]),
+    /// Vector of 64-bit signed integers
+    LongArray(&'a [i64]),
+}
+
+impl<'a> NbtValue<'a> {
+    pub fn type_name(&self) -> String {
+        match self {
+            NbtValue::Byte(_) => "byte",
+            NbtValue::Short(_) => "short",
+            NbtValue::Int(_) => "int",
+            NbtValue::Long(_) => "long",
+            NbtValue::Float(_) => "float",
+            NbtValue::Double(_) => "double",
+            NbtValue::ByteArray(_) => "byte array",
+            NbtValue::String(_) => "string",
+            NbtValue::List(_) => "list",
+            NbtValue::Compound(_) => "compound",
+            NbtValue::IntArray(_) => "int array",
+            NbtValue::LongArray(_) => "long array",
+        }.to_string()
+    }
+
+    /// The numeric id used for this type in binary NBT.
+    pub fn type_id(&self) -> i8 {
+        match self {
+            NbtValue::Byte(_) => 1,
+            NbtValue::Short(_) => 2,
+            NbtValue::Int(_) => 3,
+            NbtValue::Long(_) => 4,
+            NbtValue::Float(_) => 5,
+            NbtValue::Double(_) => 6,
+            NbtValue::ByteArray(_) => 7,
+            NbtValue::String(_) => 8,
+            NbtValue::List(_) => 9,
+            NbtValue::Compound(_) => 10,
+            NbtValue::IntArray(_) => 11,
+            NbtValue::LongArray(_) => 12,
+        }
+    }
+
+    /// Returns the named child of a compound.
+    pub fn get(&self, name: &str) -> Option<&'a NbtTag> {
+        self.as_compound().and_then(|compound| compound.get(name))
+    }
+
+    /// Returns the element at `index` of a list.
+    pub fn index(&self, index: usize) -> Option<NbtValue<'a>> {
+        self.as_list().and_then(|list| list.get(index))
+    }
+
+    pub fn as_i8(&self) -> Option<i8> {
+        if let NbtValue::Byte(value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_i16(&self) -> Option<i16> {
+        if let NbtValue::Short(value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        if let NbtValue::Int(value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        if let NbtValue::Long(value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        if let NbtValue::Float(value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        if let NbtValue::Double(value) = self { Some(*value) } else { None }
+    }
+
+    pub fn as_byte_array(&self) -> Option<&'a [i8]> {
+        if let NbtValue::ByteArray(value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        if let NbtValue::String(value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_list(&self) -> Option<&'a NbtList> {
+        if let NbtValue::List(value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_compound(&self) -> Option<&'a NbtCompound> {
+        if let NbtValue::Compound(value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_int_array(&self) -> Option<&'a [i32]> {
+        if let NbtValue::IntArray(value) = self { Some(value) } else { None }
+    }
+
+    pub fn as_long_array(&self) -> Option<&'a [i64]> {
+        if let NbtValue::LongArray(value) = self { Some(value) } else { None }
+    }
+
+    /// Copies the value into a tag with the given name.
+    pub fn to_tag<S: Into<String>>(&self, name: S) -> NbtTag {
+        let name = name.into();
+        match *self {
+            NbtValue::Byte(value) => NbtTag::Byte(name, value),
+            NbtValue::Short(value) => NbtTag::Short(name, value),
+            NbtValue::Int(value) => NbtTag::Int(name, value),
+            NbtValue::Long(value) => NbtTag::Long(name, value),
+            NbtValue::Float(value) => NbtTag::Float(name, value),
+            NbtValue::Double(value) => NbtTag::Double(name, value),
+            NbtValue::ByteArray(value) => NbtTag::ByteArray(name, value.to_vec()),
+            NbtValue::String(value) => NbtTag::String(name, value.to_string()),
+            NbtValue::List(value) => NbtTag::List(name, value.clone()),
+            NbtValue::Compound(value) => NbtTag::Compound(name, value.clone()),
+            NbtValue::IntArray(value) => NbtTag::IntArray(name, value.to_vec()),
+            NbtValue::LongArray(value) => NbtTag::LongArray(name, value.to_vec()),
+        }
+    }
+}
+
+/// Mutable access to the payload of a tag. Values can be changed in place, but not their
+/// type or the name of the tag holding them, so lists stay homogeneous and compounds keep
+/// their index. Lists and compounds are changed through their own checked methods.
+#[derive(Debug)]
+pub enum NbtValueMut<'a> {
+    Byte(&'a mut i8),
+    Short(&'a mut i16),
+    Int(&'a mut i32),
+    Long(&'a mut i64),
+    Float(&'a mut f32),
+    Double(&'a mut f64),
+    ByteArray(&'a mut Vec<i8>),
+    String(&'a mut String),
+    List(&'a mut NbtList),
+    Compound(&'a mut NbtCompound),
+    IntArray(&'a mut Vec<i32>),
+    LongArray(&'a mut Vec<i64>),
+}
+
+impl<'a> NbtValueMut<'a> {
+    /// Borrows the value for reading.
+    pub fn as_value(&self) -> NbtValue<'_> {
+        match self {
+            NbtValueMut::Byte(value) => NbtValue::Byte(**value),
+            NbtValueMut::Short(value) => NbtValue::Short(**value),
+            NbtValueMut::Int(value) => NbtValue::Int(**value),
+            NbtValueMut::Long(value) => NbtValue::Long(**value),
+            NbtValueMut::Float(value) => NbtValue::Float(**value),
+            NbtValueMut::Double(value) => NbtValue::Double(**value),
+            NbtValueMut::ByteArray(value) => NbtValue::ByteArray(value),
+            NbtValueMut::String(value) => NbtValue::String(value),
+            NbtValueMut::List(value) => NbtValue::List(value),
+            NbtValueMut::Compound(value) => NbtValue::Compound(value),
+            NbtValueMut::IntArray(value) => NbtValue::IntArray(value),
+            NbtValueMut::LongArray(value) => NbtValue::LongArray(value),
+        }
+    }
+
+    /// Replaces the value with the value of `tag`, which must have the same type. The name
+    /// of `tag` is ignored.
+    pub fn set(&mut self, tag: NbtTag) -> Result<(), NbtParseError> {
+        match (self, tag) {
+            (NbtValueMut::Byte(value), NbtTag::Byte(_, new)) => **value = new,
+            (NbtValueMut::Short(value), NbtTag::Short(_, new)) => **value = new,
+            (NbtValueMut::Int(value), NbtTag::Int(_, new)) => **value = new,
+            (NbtValueMut::Long(value), NbtTag::Long(_, new)) => **value = new,
+            (NbtValueMut::Float(value), NbtTag::Float(_, new)) => **value = new,
+            (NbtValueMut::Double(value), NbtTag::Double(_, new)) => **value = new,
+            (NbtValueMut::ByteArray(value), NbtTag::ByteArray(_, new)) => **value = new,
+            (NbtValueMut::String(value), NbtTag::String(_, new)) => **value = new,
+            (NbtValueMut::List(value), NbtTag::List(_, new)) => **value = new,
+            (NbtValueMut::Compound(value), NbtTag::Compound(_, new)) => **value = new,
+            (NbtValueMut::IntArray(value), NbtTag::IntArray(_, new)) => **value = new,
+            (NbtValueMut::LongArray(value), NbtTag::LongArray(_, new)) => **value = new,
+            (value, tag) => return Err(WrongType(value.as_value().type_name(), tag.type_name())),
+        }
+        Ok(())
+    }
+
+    pub fn into_list(self) -> Option<&'a mut NbtList> {
+        if let NbtValueMut::List(value) = self { Some(value) } else { None }
+    }
+
+    pub fn into_compound(self) -> Option<&'a mut NbtCompound> {
+        if let NbtValueMut::Compound(value) = self { Some(value) } else { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> NbtTag {
+        NbtTag::Compound("root".to_string(), vec![
+            NbtTag::Int("b".to_string(), 1),
+            NbtTag::List("list".to_string(), vec![
+                NbtTag::Compound("".to_string(), vec![
+                    NbtTag::String("a".to_string(), "x".to_string()),
+                ].into()),
+            ].try_into().unwrap()),
+            NbtTag::LongArray("longs".to_string(), vec![1, 2]),
+        ].into())
+    }
+
+    #[test]
+    fn borrow_values() {
+        let tag = tree();
+        let value = tag.as_value().unwrap();
+        assert_eq!(value.get("b").and_then(NbtTag::as_i32), Some(1));
+        let element = value.get("list").and_then(NbtTag::as_value).and_then(|list| list.index(0)).unwrap();
+        assert_eq!(element.get("a").and_then(NbtTag::as_str), Some("x"));
+        assert_eq!(element.to_tag("named"), NbtTag::Compound("named".to_string(), vec![
+            NbtTag::String("a".to_string(), "x".to_string()),
+        ].into()));
+        assert_eq!(value.get("longs").and_then(NbtTag::as_value), Some(NbtValue::LongArray(&[1, 2])));
+        assert_eq!(value.to_tag("root"), tag);
+        assert!(NbtTag::End.as_value().is_none());
+    }
+
+    #[test]
+    fn edit_values() {
+        let mut tag = NbtTag::Int("a".to_string(), 1);
+        let mut value = tag.as_value_mut().unwrap();
+        if let NbtValueMut::Int(value) = &mut value {
+            **value += 1;
+        }
+        assert_eq!(value.as_value(), NbtValue::Int(2));
+        assert!(matches!(value.set(NbtTag::Long("a".to_string(), 3)), Err(WrongType(_, _))));
+        value.set(NbtTag::Int("other".to_string(), 5)).unwrap();
+        assert_eq!(tag, NbtTag::Int("a".to_string(), 5));
+    }
+}
@@ -0,0 +1,326 @@
+use crate::NbtParseError;
+use crate::parser::{decode_bytes, decode_ints, decode_longs, Scanner};
+use crate::types::NbtTag;
+
+/// What a [`Visitor`] wants the scan to do next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visit {
+    /// Keep scanning, entering the list or compound that was just started.
+    #[default]
+    Continue,
+    /// Skip the contents of the list or compound that was just started, without an end event.
+    /// Acts like `Continue` for other events.
+    Skip,
+    /// End the scan.
+    Stop,
+}
+
+/// A number or string read by the visitor API, borrowed from the scanned data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NbtPrimitive<'a> {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(&'a str),
+}
+
+impl NbtPrimitive<'_> {
+    pub fn to_tag<S: Into<String>>(&self, name: S) -> NbtTag {
+        let name = name.into();
+        match *self {
+            NbtPrimitive::Byte(value) => NbtTag::Byte(name, value),
+            NbtPrimitive::Short(value) => NbtTag::Short(name, value),
+            NbtPrimitive::Int(value) => NbtTag::Int(name, value),
+            NbtPrimitive::Long(value) => NbtTag::Long(name, value),
+            NbtPrimitive::Float(value) => NbtTag::Float(name, value),
+            NbtPrimitive::Double(value) => NbtTag::Double(name, value),
+            NbtPrimitive::String(value) => NbtTag::String(name, value.to_string()),
+        }
+    }
+}
+
+/// A byte, int or long array read by the visitor API, still in its binary form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NbtArray<'a> {
+    pub(crate) element_type: i8,
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> NbtArray<'a> {
+    /// The type id of the array, 7, 11 or 12.
+    pub fn type_id(&self) -> i8 {
+        self.element_type
+    }
+
+    fn width(&self) -> usize {
+        match self.element_type {
+            7 => 1,
+            11 => 4,
+            _ => 8,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len() / self.width()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<i64> {
+        let width = self.width();
+        self.data.get(index * width..(index + 1) * width).map(|bytes| match bytes {
+            [byte] => *byte as i8 as i64,
+            [a, b, c, d] => i32::from_be_bytes([*a, *b, *c, *d]) as i64,
+            bytes => i64::from_be_bytes(bytes.try_into().unwrap()),
+        })
+    }
+
+    /// The elements, widened to `i64`.
+    pub fn iter(&self) -> impl Iterator<Item = i64> + 'a {
+        let array = *self;
+        (0..array.len()).map(move |index| array.get(index).unwrap())
+    }
+
+    pub fn to_tag<S: Into<String>>(&self, name: S) -> NbtTag {
+        let name = name.into();
+        match self.element_type {
+            7 => NbtTag::ByteArray(name, decode_bytes(self.data)),
+            11 => NbtTag::IntArray(name, decode_ints(self.data)),
+            _ => NbtTag::LongArray(name, decode_longs(self.data)),
+        }
+    }
+}
+
+/// Receives the events of a scan with [`nbt_visitor::visit_binary`]. Names are empty for list
+/// elements and all methods continue the scan by default.
+pub trait Visitor {
+    fn start_compound(&mut self, _name: &str) -> Visit {
+        Visit::Continue
+    }
+
+    fn end_compound(&mut self) -> Visit {
+        Visit::Continue
+    }
+
+    fn start_list(&mut self, _name: &str, _element_type: i8, _len: usize) -> Visit {
+        Visit::Continue
+    }
+
+    fn end_list(&mut self) -> Visit {
+        Visit::Continue
+    }
+
+    fn primitive(&mut self, _name: &str, _value: NbtPrimitive<'_>) -> Visit {
+        Visit::Continue
+    }
+
+    fn array(&mut self, _name: &str, _array: NbtArray<'_>) -> Visit {
+        Visit::Continue
+    }
+}
+
+/// Single-pass scanning of binary NBT without building a tree.
+pub mod nbt_visitor {
+    use super::*;
+
+    /// Scans uncompressed big-endian binary NBT, sending its contents to `visitor` in order.
+    /// Skipped and unvisited data after a stop is not validated.
+    pub fn visit_binary<V: Visitor>(data: &[u8], visitor: &mut V) -> Result<(), NbtParseError> {
+        let mut scanner = Scanner { data, pos: 0 };
+        let id = scanner.type_id()?;
+        if id != 0 {
+            let name = scanner.string()?;
+            scanner.value(id, name, visitor)?;
+        }
+        Ok(())
+    }
+}
+
+impl Scanner<'_> {
+    /// Reads the payload of a tag of type `id`, returning whether the visitor stopped.
+    fn value<V: Visitor>(&mut self, id: i8, name: &str, visitor: &mut V) -> Result<bool, NbtParseError> {
+        let visit = match id {
+            1 => visitor.primitive(name, NbtPrimitive::Byte(self.type_id()?)),
+            2 => visitor.primitive(name, NbtPrimitive::Short(i16::from_be_bytes(self.bytes()?))),
+            3 => visitor.primitive(name, NbtPrimitive::Int(i32::from_be_bytes(self.bytes()?))),
+            4 => visitor.primitive(name, NbtPrimitive::Long(i64::from_be_bytes(self.bytes()?))),
+            5 => visitor.primitive(name, NbtPrimitive::Float(f32::from_be_bytes(self.bytes()?))),
+            6 => visitor.primitive(name, NbtPrimitive::Double(f64::from_be_bytes(self.bytes()?))),
+            7 => visitor.array(name, self.array(7, 1)?),
+            8 => visitor.primitive(name, NbtPrimitive::String(self.string()?)),
+            9 => {
+                let (element_type, len) = self.list_header()?;
+                match visitor.start_list(name, element_type, len) {
+                    Visit::Continue => {
+                        for _ in 0..len {
+                            if self.value(element_type, "", visitor)? {
+                                return Ok(true);
+                            }
+                        }
+                        visitor.end_list()
+                    }
+                    Visit::Skip => {
+                        (0..len).try_for_each(|_| self.skip(element_type))?;
+                        Visit::Continue
+                    }
+                    Visit::Stop => Visit::Stop,
+                }
+            }
+            10 => match visitor.start_compound(name) {
+                Visit::Continue => loop {
+                    let id = self.type_id()?;
+                    if id == 0 {
+                        break visitor.end_compound();
+                    }
+                    let name = self.string()?;
+                    if self.value(id, name, visitor)? {
+                        return Ok(true);
+                    }
+                },
+                Visit::Skip => {
+                    self.skip(10)?;
+                    Visit::Continue
+                }
+                Visit::Stop => Visit::Stop,
+            },
+            11 => visitor.array(name, self.array(11, 4)?),
+            12 => visitor.array(name, self.array(12, 8)?),
+            id => return Err(NbtParseError::UnknownNBT(id)),
+        };
+        Ok(visit == Visit::Stop)
+    }
+
+    /// Skips the payload of a tag of type `id`.
+    pub(crate) fn skip(&mut self, id: i8) -> Result<(), NbtParseError> {
+        match id {
+            1 => self.take(1).map(drop),
+            2 => self.take(2).map(drop),
+            3 | 5 => self.take(4).map(drop),
+            4 | 6 => self.take(8).map(drop),
+            7 => self.array(7, 1).map(drop),
+            8 => self.string().map(drop),
+            9 => {
+                let (element_type, len) = self.list_header()?;
+                (0..len).try_for_each(|_| self.skip(element_type))
+            }
+            10 => loop {
+                let id = self.type_id()?;
+                if id == 0 {
+                    return Ok(());
+                }
+                self.string()?;
+                self.skip(id)?;
+            },
+            11 => self.array(11, 4).map(drop),
+            12 => self.array(12, 8).map(drop),
+            id => Err(NbtParseError::UnknownNBT(id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::nbt_visitor::visit_binary;
+    use crate::nbt_writer::write_binary;
+    use crate::snbt_parser::parse_snbt;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+        skip: Option<&'static str>,
+        stop: Option<&'static str>,
+    }
+
+    impl Visitor for Recorder {
+        fn start_compound(&mut self, name: &str) -> Visit {
+            self.events.push(format!("{{{name}"));
+            if self.skip == Some(name) { Visit::Skip } else { Visit::Continue }
+        }
+
+        fn end_compound(&mut self) -> Visit {
+            self.events.push("}".to_string());
+            Visit::Continue
+        }
+
+        fn start_list(&mut self, name: &str, element_type: i8, len: usize) -> Visit {
+            self.events.push(format!("[{name}:{element_type}:{len}"));
+            if self.skip == Some(name) { Visit::Skip } else { Visit::Continue }
+        }
+
+        fn end_list(&mut self) -> Visit {
+            self.events.push("]".to_string());
+            Visit::Continue
+        }
+
+        fn primitive(&mut self, name: &str, value: NbtPrimitive<'_>) -> Visit {
+            self.events.push(format!("{name}={value:?}"));
+            if self.stop == Some(name) { Visit::Stop } else { Visit::Continue }
+        }
+
+        fn array(&mut self, name: &str, array: NbtArray<'_>) -> Visit {
+            self.events.push(format!("{name}={}:{:?}", array.type_id(), array.iter().collect::<Vec<_>>()));
+            Visit::Continue
+        }
+    }
+
+    fn record(snbt: &str, skip: Option<&'static str>, stop: Option<&'static str>) -> Vec<String> {
+        let mut recorder = Recorder { skip, stop, ..Recorder::default() };
+        visit_binary(&write_binary(&parse_snbt(snbt).unwrap()).unwrap(), &mut recorder).unwrap();
+        recorder.events
+    }
+
+    #[test]
+    fn visit_events() {
+        let snbt = r#"{a:1b,s:"x",l:[{b:2s},{}],c:{d:[I;1,-2]},e:[L;5L],f:0.5f}"#;
+        assert_eq!(record(snbt, None, None), [
+            "{", "a=Byte(1)", "s=String(\"x\")", "[l:10:2", "{", "b=Short(2)", "}", "{", "}", "]",
+            "{c", "d=11:[1, -2]", "}", "e=12:[5]", "f=Float(0.5)", "}",
+        ]);
+        assert_eq!(record(snbt, Some("l"), Some("f")), [
+            "{", "a=Byte(1)", "s=String(\"x\")", "[l:10:2", "{c", "d=11:[1, -2]", "}", "e=12:[5]", "f=Float(0.5)",
+        ]);
+        assert_eq!(record(snbt, Some("c"), Some("s")), ["{", "a=Byte(1)", "s=String(\"x\")"]);
+
+        let data = write_binary(&parse_snbt(snbt).unwrap()).unwrap();
+        assert!(matches!(visit_binary(&data[..data.len() - 3], &mut Recorder::default()), Err(NbtParseError::EndOfData)));
+    }
+
+    #[test]
+    fn count_items() {
+        struct ItemCounter {
+            in_inventory: bool,
+            items: usize,
+        }
+
+        impl Visitor for ItemCounter {
+            fn start_compound(&mut self, _: &str) -> Visit {
+                if self.in_inventory {
+                    self.items += 1;
+                    return Visit::Skip;
+                }
+                Visit::Continue
+            }
+
+            fn start_list(&mut self, name: &str, _: i8, _: usize) -> Visit {
+                self.in_inventory = name == "Inventory";
+                if self.in_inventory { Visit::Continue } else { Visit::Skip }
+            }
+
+            fn end_list(&mut self) -> Visit {
+                self.in_inventory = false;
+                Visit::Continue
+            }
+        }
+
+        let tag = parse_snbt(r#"{Inventory:[{id:"a"},{id:"b",tag:{x:[1]}}],EnderItems:[{id:"c"}]}"#).unwrap();
+        let mut counter = ItemCounter { in_inventory: false, items: 0 };
+        visit_binary(&write_binary(&tag).unwrap(), &mut counter).unwrap();
+        assert_eq!(counter.items, 2);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::NbtParseError;
+use crate::types::NbtTag;
+use crate::value::NbtValue;
+
+pub mod nbt_writer {
+    use std::io;
+    use std::io::Write;
+    use flate2::Compression;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use crate::endian::nbt_endian;
+    use crate::parser::NbtCompression;
+    use super::*;
+
+    pub fn write_gzip(tag: &NbtTag) -> Result<Vec<u8>, NbtParseError> {
+        Ok(compress(&write_binary(tag)?, NbtCompression::Gzip)?)
+    }
+
+    pub fn write_zlib(tag: &NbtTag) -> Result<Vec<u8>, NbtParseError> {
+        Ok(compress(&write_binary(tag)?, NbtCompression::Zlib)?)
+    }
+
+    /// Writes the tag as binary NBT with the given compression.
+    pub fn write_compressed(tag: &NbtTag, compression: NbtCompression) -> Result<Vec<u8>, NbtParseError> {
+        match compression {
+            NbtCompression::None => write_binary(tag),
+            NbtCompression::Gzip => write_gzip(tag),
+            NbtCompression::Zlib => write_zlib(tag),
+        }
+    }
+
+    /// Compresses already written binary NBT.
+    pub fn compress(data: &[u8], compression: NbtCompression) -> io::Result<Vec<u8>> {
+        match compression {
+            NbtCompression::None => Ok(data.to_vec()),
+            NbtCompression::Gzip => {
+                let mut compresser = GzEncoder::new(vec![], Compression::default());
+                compresser.write_all(data)?;
+                compresser.finish()
+            }
+            NbtCompression::Zlib => {
+                let mut compresser = ZlibEncoder::new(vec![], Compression::default());
+                compresser.write_all(data)?;
+                compresser.finish()
+            }
+        }
+    }
+
+    /// Writes the tag as uncompressed little-endian binary NBT, as used by Bedrock Edition.
+    pub fn write_binary_le(tag: &NbtTag) -> Result<Vec<u8>, NbtParseError> {
+        nbt_endian::big_to_little(&write_binary(tag)?)
+    }
+
+    /// Writes the tag as uncompressed binary NBT, the inverse of `nbt_parser::parse_binary`.
+    /// Fails if a string is longer than 65535 bytes or an array or list has more than
+    /// `i32::MAX` elements, since their lengths can't be written.
+    pub fn write_binary(tag: &NbtTag) -> Result<Vec<u8>, NbtParseError> {
+        let mut data = vec![];
+        write_named(&mut data, tag)?;
+        Ok(data)
+    }
+
+    fn write_named(data: &mut Vec<u8>, tag: &NbtTag) -> Result<(), NbtParseError> {
+        data.push(tag.type_id() as u8);
+        if let Some(value) = tag.as_value() {
+            write_string(data, tag.name())?;
+            write_payload(data, value)?;
+        }
+        Ok(())
+    }
+
+    fn write_payload(data: &mut Vec<u8>, value: NbtValue<'_>) -> Result<(), NbtParseError> {
+        match value {
+            NbtValue::Byte(value) => data.extend_from_slice(&value.to_be_bytes()),
+            NbtValue::Short(value) => data.extend_from_slice(&value.to_be_bytes()),
+            NbtValue::Int(value) => data.extend_from_slice(&value.to_be_bytes()),
+            NbtValue::Long(value) => data.extend_from_slice(&value.to_be_bytes()),
+            NbtValue::Float(value) => data.extend_from_slice(&value.to_be_bytes()),
+            NbtValue::Double(value) => data.extend_from_slice(&value.to_be_bytes()),
+            NbtValue::ByteArray(vec) => {
+                write_len(data, vec.len())?;
+                data.extend(vec.iter().map(|n| *n as u8));
+            }
+            NbtValue::String(string) => write_string(data, string)?,
+            NbtValue::List(vec) => {
+                data.push(vec.element_type() as u8);
+                write_len(data, vec.len())?;
+                for value in vec {
+                    write_payload(data, value)?;
+                }
+            }
+            NbtValue::Compound(vec) => {
+                for tag in vec {
+                    // A nested End would end the compound early and cut off the rest
+                    if *tag == NbtTag::End {
+                        return Err(NbtParseError::InvalidValue("end tags can't be children of a compound".to_string()));
+                    }
+                    write_named(data, tag)?;
+                }
+                data.push(0);
+            }
+            NbtValue::IntArray(vec) => {
+                write_len(data, vec.len())?;
+                for n in vec {
+                    data.extend_from_slice(&n.to_be_bytes());
+                }
+            }
+            NbtValue::LongArray(vec) => {
+                write_len(data, vec.len())?;
+                for n in vec {
+                    data.extend_from_slice(&n.to_be_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_len(data: &mut Vec<u8>, len: usize) -> Result<(), NbtParseError> {
+        let len = i32::try_from(len).map_err(|_| NbtParseError::WrongLength(i32::MAX as usize, len))?;
+        data.extend_from_slice(&len.to_be_bytes());
+        Ok(())
+    }
+
+    fn write_string(data: &mut Vec<u8>, string: &str) -> Result<(), NbtParseError> {
+        let len = u16::try_from(string.len()).map_err(|_| NbtParseError::WrongLength(u16::MAX as usize, string.len()))?;
+        data.extend_from_slice(&len.to_be_bytes());
+        data.extend_from_slice(string.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt_parser;
+    use crate::parser::NbtCompression;
+    use crate::list::NbtList;
+
+    #[test]
+    fn write_primitives() {
+        assert_eq!(nbt_writer::write_binary(&NbtTag::Short("short".to_string(), 0xdeadu16 as i16)).unwrap(),
+                   vec![2, 0, 5, b's', b'h', b'o', b'r', b't', 0xde, 0xad]);
+        assert_eq!(nbt_writer::write_binary(&NbtTag::String("string1".to_string(), "ab".to_string())).unwrap(),
+                   vec![8, 0, 7, b's', b't', b'r', b'i', b'n', b'g', b'1', 0, 2, b'a', b'b']);
+    }
+
+    #[test]
+    fn write_nested() {
+        let tag = NbtTag::Compound("comp".to_string(), vec![
+            NbtTag::List("list".to_string(), vec![
+                NbtTag::Short("".to_string(), 0x1122),
+                NbtTag::Short("".to_string(), 0x3344),
+            ].try_into().unwrap()),
+            NbtTag::List("empty".to_string(), NbtList::new(10)),
+            NbtTag::IntArray("ints".to_string(), vec![4, -1]),
+        ].into());
+        let data = nbt_writer::write_binary(&tag).unwrap();
+        assert_eq!(&data[..7], &[10, 0, 4, b'c', b'o', b'm', b'p']);
+        assert_eq!(&data[26..34], &[b'e', b'm', b'p', b't', b'y', 10, 0, 0]);
+        assert_eq!(nbt_parser::parse_binary(data).unwrap(), tag);
+    }
+
+    #[test]
+    fn reject_long_strings() {
+        let long = "a".repeat(70000);
+        let string = NbtTag::String("s".to_string(), long.clone());
+        assert!(matches!(nbt_writer::write_binary(&string), Err(NbtParseError::WrongLength(65535, 70000))));
+        assert!(matches!(nbt_writer::write_binary_le(&string), Err(NbtParseError::WrongLength(65535, 70000))));
+        let name = NbtTag::Compound("".to_string(), vec![NbtTag::Byte(long, 1)].into());
+        assert!(matches!(nbt_writer::write_gzip(&name), Err(NbtParseError::WrongLength(65535, 70000))));
+        let longest = NbtTag::String("".to_string(), "a".repeat(65535));
+        assert_eq!(nbt_parser::parse_binary(nbt_writer::write_binary(&longest).unwrap()).unwrap(), longest);
+    }
+
+    #[test]
+    fn compressed_round_trip() {
+        let tag = NbtTag::Compound("".to_string(), vec![NbtTag::Long("long".to_string(), -5)].into());
+        let gzip = nbt_writer::write_gzip(&tag).unwrap();
+        assert_eq!(nbt_parser::parse_gzip(gzip.clone()).unwrap(), tag);
+        let zlib = nbt_writer::write_zlib(&tag).unwrap();
+        assert_eq!(nbt_parser::parse_zlib(zlib.clone()).unwrap(), tag);
+
+        assert_eq!(NbtCompression::detect(&gzip), NbtCompression::Gzip);
+        assert_eq!(NbtCompression::detect(&zlib), NbtCompression::Zlib);
+        assert_eq!(NbtCompression::detect(&nbt_writer::write_binary(&tag).unwrap()), NbtCompression::None);
+        for compression in [NbtCompression::None, NbtCompression::Gzip, NbtCompression::Zlib] {
+            let data = nbt_writer::write_compressed(&tag, compression).unwrap();
+            assert_eq!(nbt_parser::parse_any(data).unwrap(), tag);
+        }
+    }
+}